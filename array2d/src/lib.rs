@@ -402,7 +402,7 @@ impl LineIter {
         let slope_mode = dx.abs() >= dy.abs();
         let (a, b, dir, p);
         if dx == 0 && dy == 0 {
-            a = 0.0; b = 0.0; dir = 1; p = start.0;
+            a = 0.0; b = start.1 as f64; dir = 1; p = start.0;
         }else if slope_mode {
             a = dy as f64 / dx as f64;
             b = start.1 as f64 - a * start.0 as f64;
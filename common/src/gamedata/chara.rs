@@ -1,10 +1,12 @@
 
 use std::collections::HashMap;
 use objholder::CharaTemplateIdx;
+use super::defs::ElementArray;
 use super::item::{ItemList, EquipItemList};
 use super::map::MapId;
 use super::site::SiteId;
-use super::skill::SkillList;
+use super::skill::{SkillList, BaseLevel};
+use super::spell::SpellList;
 use super::unknown_id_err;
 
 /// Character's races
@@ -72,8 +74,10 @@ pub struct Chara {
     pub ai: CharaAI,
     pub hp: i32,
     pub sp: i32,
+    pub mp: i32,
     pub status: Vec<CharaStatus>,
     pub skills: SkillList,
+    pub spells: SpellList,
     /// Relationship to player character
     pub rel: Relationship,
     /// When talked, execute this script
@@ -87,6 +91,8 @@ pub struct Chara {
 pub struct CharaParams {
     /// Max HP
     pub max_hp: i32,
+    /// Max MP
+    pub max_mp: i32,
     /// Strength
     pub str: u16,
     /// Vitality
@@ -103,13 +109,22 @@ pub struct CharaParams {
     pub spd: u16,
     /// Range of view in tile
     pub view_range: i32,
+    /// Additional damage of each element on attack
+    #[serde(default)]
+    pub elemental_damage: ElementArray<u16>,
+    /// Damage reduction (%) of each element
+    #[serde(default)]
+    pub resistance: ElementArray<u16>,
+    /// Recover hp by this percentage of inflicted damage
+    #[serde(default)]
+    pub lifesteal: u16,
 }
 
 /// Character base parameters
 #[derive(Serialize, Deserialize, Default)]
 pub struct CharaBaseParams {
-    /// Character level
-    pub level: u16,
+    /// Character level and exp
+    pub level: BaseLevel,
     pub max_hp: i32,
     pub str: u16,
     pub vit: u16,
@@ -130,7 +145,26 @@ pub enum CharaStatus {
     /// Sp status
     Starving,
     Asleep { turn_left: u16 },
-    Poisoned,
+    /// Damaged every turn
+    Poisoned { turn_left: u16, strength: u16 },
+    /// Moves to wrong directions at the probability of strength (%)
+    Confused { turn_left: u16, strength: u16 },
+    /// Cannot act
+    Paralyzed { turn_left: u16 },
+    /// View range is decreased by strength
+    Blind { turn_left: u16, strength: u16 },
+    /// Damaged every turn
+    Bleeding { turn_left: u16, strength: u16 },
+    /// Damaged every turn
+    Burning { turn_left: u16, strength: u16 },
+    /// Speed is increased by strength
+    Hasted { turn_left: u16, strength: u16 },
+    /// Burden status
+    Burdened,
+    /// Burden status
+    Stressed,
+    /// Burden status
+    Overloaded,
 }
 
 impl Default for Chara {
@@ -147,8 +181,10 @@ impl Default for Chara {
             ai: CharaAI::default(),
             hp: 100,
             sp: 0,
+            mp: 0,
             status: Vec::new(),
             skills: SkillList::default(),
+            spells: SpellList::new(),
             rel: Relationship::NEUTRAL,
             trigger_talk: None,
         }
@@ -0,0 +1,29 @@
+
+use fnv::{FnvHashMap, FnvHashSet};
+use objholder::ItemIdx;
+
+/// The player's knowledge about item kinds.
+/// Appearances of unknown items are decided for each game.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct IdentifyTable {
+    known: FnvHashSet<ItemIdx>,
+    appearances: FnvHashMap<ItemIdx, String>,
+}
+
+impl IdentifyTable {
+    pub fn is_known(&self, idx: ItemIdx) -> bool {
+        self.known.contains(&idx)
+    }
+
+    pub fn set_known(&mut self, idx: ItemIdx) {
+        self.known.insert(idx);
+    }
+
+    pub fn appearance(&self, idx: ItemIdx) -> Option<&str> {
+        self.appearances.get(&idx).map(|a| a.as_str())
+    }
+
+    pub fn set_appearance(&mut self, idx: ItemIdx, appearance: String) {
+        self.appearances.insert(idx, appearance);
+    }
+}
@@ -53,6 +53,8 @@ impl Ord for Item {
         if order != Ordering::Equal { return order; }
         let order = self.rank.cmp(&other.rank);
         if order != Ordering::Equal { return order; }
+        let order = self.flags.cmp(&other.flags);
+        if order != Ordering::Equal { return order; }
         self.attributes.cmp(&other.attributes)
     }
 }
@@ -66,19 +68,23 @@ impl PartialOrd for Item {
 /// This is mainly used for item list sorting
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum ItemKind {
-    Object, Potion, Food, Weapon(WeaponKind), Armor(ArmorKind), Material, Special
+    Object, Potion, Food, Weapon(WeaponKind), Armor(ArmorKind), Material, Container, RepairKit, Special
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 #[serde(rename_all="snake_case")]
 pub enum ItemKindRough {
-    Object, Potion, Food, Weapon, Armor, Material, Special
+    Object, Potion, Food, Weapon, Armor, Material, Container, RepairKit, Special
 }
 
 bitflags! {
     pub struct ItemFlags: u64 {
         const EATABLE   = 1 << 0;
         const DRINKABLE = 1 << 1;
+        /// Marked as junk by the player
+        const JUNK      = 1 << 2;
+        /// Marked as favorite by the player
+        const FAVORITE  = 1 << 3;
     }
 }
 
@@ -111,17 +117,28 @@ impl ItemRank {
 }
 
 /// Items can have zero or more attributes.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum ItemAttribute {
     /// Data to generate the contents.
     /// Used to fix generated contents when this item is opened.
     ContentGen { level: u32, seed: u32 },
+    /// Items in the container.
+    /// The id is unique for each opened container not to stack containers sharing the contents.
+    Contents { id: u32, items: ItemList },
+    /// Prefix or suffix id which gives additional effects
+    Affix(String),
+    /// The player does not know this item's rank and affixes yet.
+    /// Potions are not identified one by one, but by the known kinds of the game.
+    Unidentified,
+    /// Sold to a shop by the player.
+    /// The shop sells it back based on the price paid for it.
+    Buyback { price: i64 },
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 #[serde(rename_all="snake_case")]
 pub enum MedicalEffect {
-    None, Heal, Sleep, Poison,
+    None, Heal, Sleep, Poison, Confuse, Paralyze, Blind, Haste,
 }
 
 impl Default for MedicalEffect {
@@ -155,12 +172,31 @@ pub enum ItemListLocation {
     Chara { cid: super::chara::CharaId },
     Equip { cid: super::chara::CharaId },
     Shop { cid: super::CharaId },
+    /// Contents of the i-th item in the parent list
+    Container { parent: ContainerParent, i: u32 },
+}
+
+/// Item lists which can have containers.
+/// Containers cannot be nested.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContainerParent {
+    OnMap { mid: super::map::MapId, pos: Vec2d },
+    Chara { cid: super::chara::CharaId },
+}
+
+impl From<ContainerParent> for ItemListLocation {
+    fn from(parent: ContainerParent) -> ItemListLocation {
+        match parent {
+            ContainerParent::OnMap { mid, pos } => ItemListLocation::OnMap { mid, pos },
+            ContainerParent::Chara { cid } => ItemListLocation::Chara { cid },
+        }
+    }
 }
 
 pub type ItemLocation = (ItemListLocation, u32);
 
 /// Item list that records all items owned by one character or one tile
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub struct ItemList {
     pub items: Vec<(Item, u32)>,
 }
@@ -182,23 +218,28 @@ impl ItemList {
         self.items.is_empty()
     }
 
-    /// Append item
-    pub fn append(&mut self, item: Item, n: u32) {
+    /// Get the number of item stacks
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Append item, and returns its index in this list
+    pub fn append(&mut self, item: Item, n: u32) -> u32 {
         
         if self.items.is_empty() {
             self.items.push((item, n));
-            return;
+            return 0;
         }
 
         for i in 0..self.items.len() {
             match item.cmp(&self.items[i].0) { 
                 Ordering::Equal => { // If this list has the same item, increases the number
                     self.items[i].1 += n;
-                    return;
+                    return i as u32;
                 }
                 Ordering::Less => {
                     self.items.insert(i, (item, n));
-                    return;
+                    return i as u32;
                 }
                 Ordering::Greater => {
                     continue;
@@ -206,6 +247,7 @@ impl ItemList {
             }
         }
         self.items.push((item, n));
+        self.items.len() as u32 - 1
     }
 
     /// Remove an item from list
@@ -251,6 +293,18 @@ impl ItemList {
         dest.append(item, n);
     }
 
+    /// Split n items from the i-th stack into a new stack placed next to it.
+    /// Returns the index of the new stack.
+    pub fn split(&mut self, i: u32, n: u32) -> u32 {
+        let i = i as usize;
+        assert!(self.items[i].1 > n && n != 0);
+
+        self.items[i].1 -= n;
+        let item = self.items[i].0.clone();
+        self.items.insert(i + 1, (item, n));
+        i as u32 + 1
+    }
+
     /// Clear all item in list
     pub fn clear(&mut self) {
         self.items.clear();
@@ -268,7 +322,7 @@ pub enum ItemMoveNum {
 }
 
 impl ItemMoveNum {
-    fn to_u32(self, all: u32) -> u32 {
+    pub fn to_u32(self, all: u32) -> u32 {
         match self {
             ItemMoveNum::All => all,
             ItemMoveNum::Partial(n) => n,
@@ -292,7 +346,79 @@ pub enum EquipSlotKind {
     MeleeWeapon, RangedWeapon, BodyArmor, Shield,
 }
 
+impl Item {
+    /// Item of the normal rank without attributes
+    pub fn new(idx: ItemIdx, kind: ItemKind, flags: ItemFlags) -> Item {
+        Item {
+            idx,
+            kind,
+            flags,
+            rank: ItemRank::default(),
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Contents of the container. Returns None if the container is not opened yet.
+    pub fn contents(&self) -> Option<&ItemList> {
+        self.attributes.iter().filter_map(|attr| match attr {
+            ItemAttribute::Contents { ref items, .. } => Some(items),
+            _ => None,
+        }).next()
+    }
+
+    /// Ids of affixes of this item
+    pub fn affixes(&self) -> Vec<&str> {
+        self.attributes.iter().filter_map(|attr| match attr {
+            ItemAttribute::Affix(id) => Some(id.as_str()),
+            _ => None,
+        }).collect()
+    }
+
+    pub fn is_identified(&self) -> bool {
+        !self.attributes.iter().any(|attr| match attr {
+            ItemAttribute::Unidentified => true,
+            _ => false,
+        })
+    }
+
+    pub fn identify(&mut self) {
+        self.attributes.retain(|attr| match attr {
+            ItemAttribute::Unidentified => false,
+            _ => true,
+        });
+    }
+
+    /// The price the shop paid when the player sold this item
+    pub fn buyback_price(&self) -> Option<i64> {
+        self.attributes.iter().filter_map(|attr| match attr {
+            ItemAttribute::Buyback { price } => Some(*price),
+            _ => None,
+        }).next()
+    }
+
+    pub fn contents_mut(&mut self) -> Option<&mut ItemList> {
+        self.attributes.iter_mut().filter_map(|attr| match attr {
+            ItemAttribute::Contents { ref mut items, .. } => Some(items),
+            _ => None,
+        }).next()
+    }
+}
+
 impl ItemKind {
+    pub fn rough(self) -> ItemKindRough {
+        match self {
+            ItemKind::Object => ItemKindRough::Object,
+            ItemKind::Potion => ItemKindRough::Potion,
+            ItemKind::Food => ItemKindRough::Food,
+            ItemKind::Weapon(_) => ItemKindRough::Weapon,
+            ItemKind::Armor(_) => ItemKindRough::Armor,
+            ItemKind::Material => ItemKindRough::Material,
+            ItemKind::Container => ItemKindRough::Container,
+            ItemKind::RepairKit => ItemKindRough::RepairKit,
+            ItemKind::Special => ItemKindRough::Special,
+        }
+    }
+
     pub fn equip_slot_kind(self) -> Option<EquipSlotKind> {
         match self {
             ItemKind::Weapon(weapon_kind) => Some(weapon_kind.equip_slot_kind()),
@@ -389,6 +515,16 @@ impl EquipItemList {
         }
     }
     
+    /// Mutable version for item
+    pub fn item_mut(&mut self, esk: EquipSlotKind, n: usize) -> Option<&mut Item> {
+        assert!(n < MAX_SLOT_NUM_PER_KIND);
+        if let Some(a) = self.list_idx(esk, n) {
+            Some(&mut self.item_list.items[a].0)
+        } else {
+            None
+        }
+    }
+    
     /// Equip an item to specified slot (the nth slot of given ItemKind), and returns removed item
     pub fn equip(&mut self, esk: EquipSlotKind, n: usize, item: Item) -> Option<Item> {
         assert!(self.slot_num(esk) > n);
@@ -463,6 +599,11 @@ impl EquipItemList {
         &self.item_list
     }
 
+    /// Get equipped item by the index in the list
+    pub fn list_item_mut(&mut self, i: u32) -> &mut Item {
+        &mut self.item_list.items[i as usize].0
+    }
+
     pub fn n_slots(&self) -> u32 {
         self.slots.len() as u32
     }
@@ -550,3 +691,30 @@ mod impl_serde {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(idx: u32) -> Item {
+        Item::new(ItemIdx(idx), ItemKind::Object, ItemFlags::empty())
+    }
+
+    #[test]
+    fn split_item_list() {
+        let mut list = ItemList::new();
+        list.append(item(0), 5);
+        list.append(item(1), 1);
+
+        // The split stack is inserted just after the original stack
+        assert_eq!(list.split(0, 2), 1);
+        assert_eq!(list.len(), 3);
+        assert_eq!((list.items[0].0.idx, list.get_number(0)), (ItemIdx(0), 3));
+        assert_eq!((list.items[1].0.idx, list.get_number(1)), (ItemIdx(0), 2));
+        assert_eq!((list.items[2].0.idx, list.get_number(2)), (ItemIdx(1), 1));
+
+        // Appending the same item merges into the first stack
+        list.append(item(0), 1);
+        assert_eq!(list.get_number(0), 4);
+        assert_eq!(list.get_number(1), 2);
+    }
+}
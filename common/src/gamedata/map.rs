@@ -24,6 +24,9 @@ pub struct Map {
     /// If this is None, nearest tile's infomation will be used
     pub outside_tile: Option<OutsideTileInfo>,
     pub boundary: MapBoundary,
+    /// The day when the player left this map last
+    #[serde(default)]
+    pub last_visit: Option<u32>,
 }
 
 /// Represents tile image layers
@@ -92,7 +95,28 @@ pub enum SpecialTileKind {
     /// Site symbol on region map
     SiteSymbol {
         kind: SiteSymbolKind,
-    }
+    },
+    /// Trap is triggered when a character steps on it
+    Trap {
+        kind: TrapKind,
+        /// Hidden traps are not displayed until detected
+        hidden: bool,
+    },
+    /// Closed doors block movement and view.
+    /// If lock_level is not 0, the door needs to be unlocked before opening.
+    Door {
+        open: bool,
+        lock_level: u16,
+    },
+    /// Lever opens or closes the door at the target position
+    Lever {
+        on: bool,
+        target: Vec2d,
+    },
+    /// Items on container tile cannot be seen or picked up while it is locked
+    Container {
+        lock_level: u16,
+    },
 }
 
 impl SpecialTileKind {
@@ -102,6 +126,31 @@ impl SpecialTileKind {
             _ => false,
         }
     }
+
+    /// Closed doors and locked containers cannot be passed through
+    pub fn is_passable(&self) -> bool {
+        match *self {
+            SpecialTileKind::Door { open, .. } => open,
+            SpecialTileKind::Container { lock_level } => lock_level == 0,
+            _ => true,
+        }
+    }
+
+    /// Closed doors block characters' view
+    pub fn is_blocking_view(&self) -> bool {
+        match *self {
+            SpecialTileKind::Door { open, .. } => !open,
+            _ => false,
+        }
+    }
+
+    /// Returns the kind as characters see it. Hidden traps are not seen.
+    pub fn observed(&self) -> SpecialTileKind {
+        match *self {
+            SpecialTileKind::Trap { hidden: true, .. } => SpecialTileKind::None,
+            _ => *self,
+        }
+    }
 }
 
 impl Default for SpecialTileKind {
@@ -121,6 +170,16 @@ pub enum SiteSymbolKind {
     Cave, Ruin, Tower, Town, Village,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum TrapKind {
+    Damage, Poison, Teleport, Alarm,
+    /// Causes bleeding
+    Spike,
+    /// Sets the character on fire
+    Fire,
+}
+
 impl SpecialTileKind {
     /// Convert to id of SpecialTileObject
     pub fn obj_id(&self) -> Option<&'static str> {
@@ -141,6 +200,22 @@ impl SpecialTileKind {
                     SiteSymbolKind::Village => "!rm-village",
                 }
             }
+            SpecialTileKind::Trap { hidden: true, .. } => { return None; },
+            SpecialTileKind::Trap { kind, .. } => {
+                match kind {
+                    TrapKind::Damage =>   "!trap-damage",
+                    TrapKind::Poison =>   "!trap-poison",
+                    TrapKind::Teleport => "!trap-teleport",
+                    TrapKind::Spike =>    "!trap-spike",
+                    TrapKind::Fire =>     "!trap-fire",
+                    TrapKind::Alarm =>    "!trap-alarm",
+                }
+            }
+            SpecialTileKind::Door { open: true, .. } => "!door-open",
+            SpecialTileKind::Door { open: false, .. } => "!door-closed",
+            SpecialTileKind::Lever { on: true, .. } => "!lever-on",
+            SpecialTileKind::Lever { on: false, .. } => "!lever-off",
+            SpecialTileKind::Container { .. } => "!container",
         })
     }
 }
@@ -223,6 +298,7 @@ impl Map {
             charaid: Vec::new(),
             outside_tile: None,
             boundary: MapBoundary::default(),
+            last_visit: None,
         }
     }
 
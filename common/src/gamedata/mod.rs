@@ -1,6 +1,7 @@
 
 pub mod chara;
 pub mod defs;
+pub mod identify;
 pub mod item;
 pub mod map;
 pub mod player;
@@ -8,6 +9,7 @@ pub mod region;
 pub mod shop;
 pub mod site;
 pub mod skill;
+pub mod spell;
 pub mod time;
 pub mod town;
 pub mod variables;
@@ -16,6 +18,7 @@ use array2d::Vec2d;
 
 pub use self::chara::*;
 pub use self::defs::*;
+pub use self::identify::*;
 pub use self::item::*;
 pub use self::map::*;
 pub use self::player::*;
@@ -23,6 +26,7 @@ pub use self::region::*;
 pub use self::shop::*;
 pub use self::site::*;
 pub use self::skill::*;
+pub use self::spell::*;
 pub use self::time::*;
 pub use self::town::*;
 pub use self::variables::*;
@@ -36,6 +40,16 @@ pub struct GameData {
     pub time: Time,
     pub player: Player,
     pub vars: Variables,
+    pub identify: IdentifyTable,
+    /// Seed for procedural generation of the world
+    pub world_seed: u32,
+    /// Identifies the save of this game.
+    /// Files of unloaded sites are stored in the directory named by this.
+    #[serde(default)]
+    pub save_id: String,
+    /// Used to give unique ids to opened containers
+    #[serde(default)]
+    last_container_id: u32,
     current_mapid: MapId,
 }
 
@@ -47,6 +61,10 @@ impl GameData {
             time: Time::default(),
             player: Player::default(),
             vars: Variables::new(),
+            identify: IdentifyTable::default(),
+            world_seed: 0,
+            save_id: String::new(),
+            last_container_id: 0,
             current_mapid: MapId::default(),
         }
     }
@@ -128,6 +146,46 @@ impl GameData {
         region.add_site(site, kind, pos)
     }
 
+    /// Remove the site and characters on its maps
+    pub fn remove_site(&mut self, sid: SiteId) {
+        let site = if let Some(site) = self.region.get_mut(sid.rid).remove_site(sid) {
+            site
+        } else {
+            return;
+        };
+        for map in site.maps() {
+            for cid in map.iter_charaid() {
+                if *cid != CharaId::Player {
+                    self.chara.remove_chara(*cid);
+                }
+            }
+        }
+    }
+
+    /// Take maps and characters on them out of the site.
+    /// They must be restored by reload_site before accessing the site's maps.
+    pub fn unload_site(&mut self, sid: SiteId) -> UnloadedSite {
+        let maps = self.region.get_site_mut(sid).take_maps();
+        let mut charas = Vec::new();
+        for map in &maps {
+            for cid in map.iter_charaid() {
+                if *cid == CharaId::Player { continue; }
+                if let Some(chara) = self.chara.0.remove(cid) {
+                    charas.push((*cid, chara));
+                }
+            }
+        }
+        UnloadedSite { maps, charas }
+    }
+
+    /// Restore unloaded maps and characters to the site
+    pub fn reload_site(&mut self, sid: SiteId, unloaded: UnloadedSite) {
+        for (cid, chara) in unloaded.charas.into_iter() {
+            self.chara.0.insert(cid, chara);
+        }
+        self.region.get_site_mut(sid).restore_maps(unloaded.maps);
+    }
+
     pub fn add_map(&mut self, map: Map, sid: SiteId) -> MapId {
         let site = self.region.get_site_mut(sid);
         let floor = site.add_map(map);
@@ -155,6 +213,10 @@ impl GameData {
             ItemListLocation::Shop { cid } => {
                 &self.get_shop(cid).items
             }
+            ItemListLocation::Container { parent, i } => {
+                self.get_item_list(parent.into()).items[i as usize].0.contents()
+                    .expect("Get item list to unopened container")
+            }
         }
     }
 
@@ -174,6 +236,10 @@ impl GameData {
             ItemListLocation::Shop { cid } => {
                 &mut self.get_shop_mut(cid).items
             }
+            ItemListLocation::Container { parent, i } => {
+                self.get_item_list_mut(parent.into()).items[i as usize].0.contents_mut()
+                    .expect("Get item list to unopened container")
+            }
         }
     }
 
@@ -225,6 +291,88 @@ impl GameData {
         self.check_item_list_on_tile(item_location.0);
     }
 
+    /// Append item to the list, and returns its location.
+    /// If the list is on a tile without items, it is created.
+    pub fn append_item(&mut self, list_location: ItemListLocation, item: Item, n: u32) -> ItemLocation {
+        self.create_item_list_on_tile(list_location);
+        let i = self.get_item_list_mut(list_location).append(item, n);
+        (list_location, i)
+    }
+
+    /// Give an unique id for a newly opened container
+    pub fn new_container_id(&mut self) -> u32 {
+        self.last_container_id += 1;
+        self.last_container_id
+    }
+
+    /// Move items from the container to dest.
+    /// Returns the container location, which is changed if dest is its parent list.
+    pub fn take_out_item(&mut self, item_location: ItemLocation, dest: ItemListLocation, n: u32)
+                         -> ItemListLocation {
+        let mut taken = None;
+        let container = self.modify_contents(item_location.0, |items| {
+            taken = Some(items.remove_and_get(item_location.1, n));
+        });
+        let item = taken.unwrap();
+        let (parent, i) = match container {
+            ItemListLocation::Container { parent, i } => (parent, i),
+            _ => unreachable!(),
+        };
+
+        let parent_ill: ItemListLocation = parent.into();
+        if dest != parent_ill {
+            self.append_item(dest, item, n);
+            return container;
+        }
+
+        let list = self.get_item_list_mut(dest);
+        let len = list.len();
+        let j = list.append(item, n);
+        if list.len() > len && j <= i {
+            ItemListLocation::Container { parent, i: i + 1 }
+        } else {
+            container
+        }
+    }
+
+    /// Move items from the src to the container.
+    /// Returns the container location, which is changed if the src is its parent list.
+    pub fn put_in_item(&mut self, item_location: ItemLocation, container: ItemListLocation, n: u32)
+                       -> ItemListLocation {
+        let (parent, i) = match container {
+            ItemListLocation::Container { parent, i } => (parent, i),
+            _ => panic!("Tried to put items into not a container"),
+        };
+
+        let parent_ill: ItemListLocation = parent.into();
+        let len = self.get_item_list(item_location.0).len();
+        let item = self.remove_item_and_get(item_location, n);
+        let removed = item_location.0 == parent_ill && self.get_item_list(item_location.0).len() < len;
+        let container = if removed && item_location.1 < i {
+            ItemListLocation::Container { parent, i: i - 1 }
+        } else {
+            container
+        };
+        self.modify_contents(container, |items| { items.append(item, n); })
+    }
+
+    /// Modify the contents of the container.
+    /// The container is appended to its parent list again to keep the list sorted,
+    /// so returns the new container location.
+    fn modify_contents<F: FnOnce(&mut ItemList)>(&mut self, container: ItemListLocation, f: F)
+                                                 -> ItemListLocation {
+        let (parent, i) = match container {
+            ItemListLocation::Container { parent, i } => (parent, i),
+            _ => panic!("Tried to modify contents of not a container"),
+        };
+
+        let list = self.get_item_list_mut(parent.into());
+        let mut item = list.remove_and_get(i, 1);
+        f(item.contents_mut().expect("Modify contents of unopened container"));
+        let i = list.append(item, 1);
+        ItemListLocation::Container { parent, i }
+    }
+
     /// Checks item list on tile is empty or not. If so, delete
     fn check_item_list_on_tile(&mut self, item_list_location: ItemListLocation) {
         match item_list_location {
@@ -295,3 +443,76 @@ fn unknown_id_err<T: ::std::fmt::Debug>(id: T) -> ! {
     panic!("Internal error: Unknown id - {:?}", id)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use objholder::ItemIdx;
+
+    fn item(idx: u32, kind: ItemKind) -> Item {
+        Item::new(ItemIdx(idx), kind, ItemFlags::empty())
+    }
+
+    fn container_at(i: u32) -> ItemListLocation {
+        ItemListLocation::Container { parent: ContainerParent::Chara { cid: CharaId::Player }, i }
+    }
+
+    /// The player has an object and a container which has two other objects
+    fn gamedata_with_container() -> (GameData, ItemListLocation) {
+        let mut gd = GameData::empty();
+        gd.add_chara(Chara::default(), CharaKind::Player);
+
+        let mut contents = ItemList::new();
+        contents.append(item(0, ItemKind::Object), 2);
+        let mut container = item(1, ItemKind::Container);
+        container.attributes.push(ItemAttribute::Contents { id: 1, items: contents });
+
+        let ill = ItemListLocation::Chara { cid: CharaId::Player };
+        gd.get_item_list_mut(ill).append(item(5, ItemKind::Object), 1);
+        let i = gd.get_item_list_mut(ill).append(container, 1);
+        assert_eq!(i, 1);
+        (gd, ill)
+    }
+
+    #[test]
+    fn take_out_item_from_container() {
+        let (mut gd, ill) = gamedata_with_container();
+        let container = container_at(1);
+
+        // The taken item is inserted before the container
+        let container = gd.take_out_item((container, 0), ill, 1);
+        assert_eq!(container, container_at(2));
+        assert_eq!(gd.get_item((ill, 0)).0.idx, ItemIdx(0));
+        assert_eq!(gd.get_item((container, 0)).1, 1);
+
+        // The taken item is merged into the existing stack
+        let container = gd.take_out_item((container, 0), ill, 1);
+        assert_eq!(container, container_at(2));
+        assert_eq!(gd.get_item((ill, 0)).1, 2);
+        assert!(gd.get_item_list(container).is_empty());
+    }
+
+    #[test]
+    fn opened_containers_are_not_stacked() {
+        let mut list = ItemList::new();
+        for id in 1..3 {
+            let mut container = item(1, ItemKind::Container);
+            container.attributes.push(ItemAttribute::Contents { id, items: ItemList::new() });
+            list.append(container, 1);
+        }
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn put_in_item_to_container() {
+        let (mut gd, ill) = gamedata_with_container();
+        let container = container_at(1);
+
+        // The stack before the container is removed
+        let container = gd.put_in_item((ill, 0), container, 1);
+        assert_eq!(container, container_at(0));
+        assert_eq!(gd.get_item((ill, 0)).0.kind, ItemKind::Container);
+        assert_eq!(gd.get_item_list(container).len(), 2);
+        assert_eq!(gd.get_item((container, 1)).0.idx, ItemIdx(5));
+    }
+}
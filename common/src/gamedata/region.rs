@@ -70,6 +70,16 @@ impl RegionHolder {
         region.sites.get(&sid).unwrap_or_else(|| unknown_id_err(sid)).pos
     }
 
+    /// Iterate over all sites in all regions
+    pub fn iter_sites(&self) -> impl Iterator<Item=&Site> {
+        self.0.values().flat_map(|region| region.sites.values().map(|site_info| &site_info.site))
+    }
+
+    /// Get ids of sites of the given kind in all regions
+    pub fn get_site_ids(&self, kind: SiteKind) -> Vec<SiteId> {
+        self.0.values().flat_map(|region| region.get_site_ids(kind)).collect()
+    }
+
     pub fn get_map(&self, mid: MapId) -> &Map {
         match mid {
             MapId::SiteMap { sid, floor } => { self.get_site(sid).get_map(floor) }
@@ -170,11 +180,21 @@ impl Region {
         Some(sid)
     }
 
+    /// Remove the site from region
+    pub(crate) fn remove_site(&mut self, sid: SiteId) -> Option<Site> {
+        self.sites.remove(&sid).map(|site_info| site_info.site)
+    }
+
     /// Get the number of sites on the region
     pub fn get_site_n(&self, kind: SiteKind) -> u32 {
         self.sites.keys().filter(|&sid| sid.kind == kind).count() as u32
     }
 
+    /// Get ids of sites of the given kind
+    pub fn get_site_ids(&self, kind: SiteKind) -> Vec<SiteId> {
+        self.sites.keys().filter(|&sid| sid.kind == kind).cloned().collect()
+    }
+
     /// Get site by position on the region
     pub fn get_id_by_pos(&self, pos: Vec2d) -> Option<SiteId> {
         for (sid, sinfo) in self.sites.iter() {
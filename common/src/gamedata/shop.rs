@@ -1,5 +1,7 @@
 
 use gamedata::item::ItemList;
+use objholder::ItemIdx;
+use fnv::FnvHashMap;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[serde(rename_all="snake_case")]
@@ -8,6 +10,39 @@ pub enum ShopKind {
     Equipment,
     /// Sells potions
     Potion,
+    /// Sells weapons, armors and repair kits
+    Blacksmith,
+    /// Sells miscellaneous items
+    GeneralStore,
+    /// Sells food
+    Food,
+    /// Sells materials
+    Material,
+    /// Heals injuries
+    Healer,
+    /// Identifies items
+    Appraiser,
+    /// Keeps the player's items
+    Storage,
+    /// The player can stay for rest
+    Inn,
+}
+
+impl ShopKind {
+    /// Shops only offering services do not sell items
+    pub fn sells_items(self) -> bool {
+        match self {
+            ShopKind::Healer | ShopKind::Appraiser | ShopKind::Storage | ShopKind::Inn => false,
+            _ => true,
+        }
+    }
+}
+
+/// Services offered at shops
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum ServiceKind {
+    Heal, Identify, Repair, Storage, Inn,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -16,5 +51,36 @@ pub struct Shop {
     pub items: ItemList,
     /// Shop level is used to choose shop items
     pub level: u32,
+    /// Money to buy items from the player
+    pub money: i64,
+    /// Items are restocked on this day
+    pub restock_day: u32,
+    /// The number of items the player has sold for each kind
+    pub sold: FnvHashMap<ItemIdx, u32>,
 }
 
+impl Shop {
+    pub fn new(kind: ShopKind, level: u32) -> Shop {
+        Shop {
+            kind,
+            items: ItemList::new(),
+            level,
+            money: 0,
+            restock_day: 0,
+            sold: FnvHashMap::default(),
+        }
+    }
+
+    /// Supply of the item kind is the number in stock and sold by the player
+    pub fn supply(&self, idx: ItemIdx) -> u32 {
+        let n_stock: u32 = self.items.iter()
+            .filter(|&&(ref item, _)| item.idx == idx)
+            .map(|&(_, n)| n)
+            .sum();
+        n_stock + self.sold.get(&idx).cloned().unwrap_or(0)
+    }
+
+    pub fn add_sold(&mut self, idx: ItemIdx, n: u32) {
+        *self.sold.entry(idx).or_insert(0) += n;
+    }
+}
@@ -1,4 +1,6 @@
 
+use std::io::{Read, Write};
+use super::chara::{Chara, CharaId};
 use super::map::Map;
 use super::region::RegionId;
 use super::town::Town;
@@ -13,6 +15,16 @@ pub struct Site {
     max_floor: u32,
     /// Site kind specific data
     pub content: SiteContent,
+    /// The day when this site was cleared
+    #[serde(default)]
+    pub cleared_day: Option<u32>,
+    /// The number of floors while maps are unloaded
+    #[serde(default)]
+    n_unloaded_floor: Option<u32>,
+    /// The file name of unloaded maps.
+    /// Every unloading uses a new file not to break other saves referring older files.
+    #[serde(default)]
+    pub unloaded_file: Option<String>,
 }
 
 /// Site kind specific data
@@ -23,6 +35,9 @@ pub enum SiteContent {
     AutoGenDungeon {
         /// It is used for map generation, enemy race weighting, etc.
         dungeon_kind: DungeonKind,
+        /// Theme id in the dungeon generation rules
+        #[serde(default)]
+        theme: Option<String>,
     },
     /// Town consists of residents and shops, etc.
     Town {
@@ -40,6 +55,9 @@ impl Site {
             map: Vec::new(),
             max_floor,
             content: SiteContent::Other,
+            cleared_day: None,
+            n_unloaded_floor: None,
+            unloaded_file: None,
         }
     }
     
@@ -75,7 +93,31 @@ impl Site {
     }
 
     pub fn floor_num(&self) -> u32 {
-        self.map.len() as u32
+        if let Some(n) = self.n_unloaded_floor {
+            n
+        } else {
+            self.map.len() as u32
+        }
+    }
+
+    /// Maps of this site are unloaded, and need to be restored before using
+    pub fn is_unloaded(&self) -> bool {
+        self.n_unloaded_floor.is_some()
+    }
+
+    /// Take all maps out. The number of floors is kept until maps are restored.
+    pub(crate) fn take_maps(&mut self) -> Vec<Map> {
+        self.n_unloaded_floor = Some(self.map.len() as u32);
+        ::std::mem::replace(&mut self.map, Vec::new())
+    }
+
+    pub(crate) fn restore_maps(&mut self, maps: Vec<Map>) {
+        self.map = maps;
+        self.n_unloaded_floor = None;
+    }
+
+    pub(crate) fn maps(&self) -> &[Map] {
+        &self.map
     }
 
     pub fn max_floor(&self) -> u32 {
@@ -83,6 +125,25 @@ impl Site {
     }
 }
 
+/// Maps and characters on them taken out of a site to be stored to disk
+#[derive(Default, Serialize, Deserialize)]
+pub struct UnloadedSite {
+    pub(crate) maps: Vec<Map>,
+    pub(crate) charas: Vec<(CharaId, Chara)>,
+}
+
+impl UnloadedSite {
+    /// Write as msgpack
+    pub fn write<W: Write>(&self, w: &mut W) -> Result<(), ::rmps::encode::Error> {
+        ::rmps::encode::write(w, self)
+    }
+
+    /// Read from msgpack bytes
+    pub fn read<R: Read>(r: R) -> Result<UnloadedSite, ::rmps::decode::Error> {
+        ::rmps::decode::from_read(r)
+    }
+}
+
 impl SiteContent {
     pub fn kind(&self) -> SiteKind {
         match self {
@@ -116,6 +177,12 @@ impl Default for SiteId {
     }
 }
 
+impl ::std::fmt::Display for SiteId {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "{}-{:?}-{}", self.rid.0, self.kind, self.n)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum DungeonKind {
     None, Cave, Ruin,
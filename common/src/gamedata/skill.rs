@@ -1,19 +1,50 @@
 
 use fnv::FnvHashMap;
 use super::item::WeaponKind;
+use super::spell::MagicKind;
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
 #[serde(rename_all="snake_case")]
 pub enum SkillKind {
     Defence,
     MartialArts,
     Weapon(WeaponKind),
+    Magic(MagicKind),
+    /// Non-combat skill defined in rules by its id
+    NonCombat(String),
+}
+
+impl SkillKind {
+    pub fn non_combat(id: &str) -> SkillKind {
+        SkillKind::NonCombat(id.to_owned())
+    }
+
+    /// Returns the skill id if this is a non-combat skill
+    pub fn non_combat_id(&self) -> Option<&str> {
+        match *self {
+            SkillKind::NonCombat(ref id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Ids of non-combat skills which the game refers to
+pub mod skill_id {
+    pub const APPRAISAL: &str = "appraisal";
+    pub const DETECTION: &str = "detection";
+    pub const HEALING: &str = "healing";
+    pub const LOCKPICKING: &str = "lockpicking";
+    pub const STEALTH: &str = "stealth";
+    pub const TRADING: &str = "trading";
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SkillList {
     pub skills: FnvHashMap<SkillKind, u16>,
-    pub exp: Option<FnvHashMap<SkillKind, u16>>
+    pub exp: Option<FnvHashMap<SkillKind, u16>>,
+    /// Bonus levels given by equipments
+    #[serde(default)]
+    pub bonus: FnvHashMap<SkillKind, u16>,
 }
 
 impl Default for SkillList {
@@ -21,21 +52,26 @@ impl Default for SkillList {
         SkillList {
             skills: FnvHashMap::default(),
             exp: None,
+            bonus: FnvHashMap::default(),
         }
     }
 }
 
 impl SkillList {
-    pub fn get(&self, kind: SkillKind) -> u16 {
-        if let Some(skill) = self.skills.get(&kind) {
-            *skill
+    /// Get skill level including bonus.
+    /// Bonus is applied to learned skills only.
+    pub fn get(&self, kind: &SkillKind) -> u16 {
+        let bonus = self.bonus.get(kind).cloned().unwrap_or(0);
+        if let Some(skill) = self.skills.get(kind) {
+            skill.saturating_add(bonus)
         } else {
             0
         }
     }
 }
 
-/// Used to adjust exp of skills when getting exp
+/// Character's base level and exp.
+/// Also used to adjust exp of skills when getting exp
 #[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
 pub struct BaseLevel {
     level: u16,
@@ -43,19 +79,54 @@ pub struct BaseLevel {
 }
 
 impl BaseLevel {
+    pub fn new(level: u16) -> BaseLevel {
+        BaseLevel { level, exp: 0 }
+    }
+
     pub fn level(&self) -> u16 {
         self.level
     }
 
-    pub fn add_exp(&mut self, exp: u16) {
+    pub fn exp(&self) -> u16 {
+        self.exp
+    }
+
+    /// Add exp. Exp over the threshold is carried over to the next level.
+    /// Returns the number of gained levels.
+    pub fn add_exp(&mut self, exp: u32) -> u32 {
         use basic::BASE_LEVEL_EXP_LVUP;
 
-        if self.exp + exp <= BASE_LEVEL_EXP_LVUP {
+        let mut sum = self.exp as u32 + exp;
+        let mut gained_levels = 0;
+        while sum >= BASE_LEVEL_EXP_LVUP as u32 {
+            sum -= BASE_LEVEL_EXP_LVUP as u32;
             self.level += 1;
-            self.exp = 0;
-        } else {
-            self.exp += exp;
+            gained_levels += 1;
         }
+        self.exp = sum as u16;
+        gained_levels
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use basic::BASE_LEVEL_EXP_LVUP;
+
+    #[test]
+    fn base_level_add_exp() {
+        let lvup = BASE_LEVEL_EXP_LVUP as u32;
+        let mut level = BaseLevel::new(1);
+
+        assert_eq!(level.add_exp(lvup - 1), 0);
+        assert_eq!((level.level(), level.exp()), (1, BASE_LEVEL_EXP_LVUP - 1));
+
+        // Overflowed exp is kept
+        assert_eq!(level.add_exp(11), 1);
+        assert_eq!((level.level(), level.exp()), (2, 10));
+
+        // Multiple levels at once
+        assert_eq!(level.add_exp(lvup * 3), 3);
+        assert_eq!((level.level(), level.exp()), (5, 10));
+    }
+}
@@ -0,0 +1,87 @@
+
+use objholder::SpellIdx;
+use super::defs::Element;
+
+/// Spell object, compiled by makepak
+#[derive(Serialize, Deserialize)]
+pub struct SpellObject {
+    pub id: String,
+    /// The kind of magic skill used for this spell
+    pub magic_kind: MagicKind,
+    /// Consumed mp when casted
+    pub cost: i32,
+    pub target: SpellTarget,
+    pub element: Element,
+    pub effect: SpellEffect,
+    /// Effect power of this spell. Damage dice or healing value is calculated from it.
+    pub power: u16,
+}
+
+/// Kinds of magic. Each kind has its own skill.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum MagicKind {
+    Destruction,
+    Restoration,
+    Enchantment,
+}
+
+/// Targeting mode of spells
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum SpellTarget {
+    /// The caster itself
+    Caster,
+    /// Current target character
+    Target,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum SpellEffect {
+    Damage,
+    Heal,
+    Sleep,
+    Poison,
+}
+
+/// Spells a character has learned
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct SpellList {
+    spells: Vec<SpellIdx>,
+}
+
+impl SpellList {
+    pub fn new() -> SpellList {
+        SpellList::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.spells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spells.is_empty()
+    }
+
+    pub fn has(&self, idx: SpellIdx) -> bool {
+        self.spells.contains(&idx)
+    }
+
+    /// Learn a new spell. Returns false if this spell is already learned.
+    pub fn learn(&mut self, idx: SpellIdx) -> bool {
+        if self.has(idx) {
+            return false;
+        }
+        self.spells.push(idx);
+        true
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<SpellIdx> {
+        self.spells.iter()
+    }
+
+    pub fn get(&self, i: usize) -> Option<SpellIdx> {
+        self.spells.get(i).cloned()
+    }
+}
@@ -69,6 +69,11 @@ impl Time {
         self.year
     }
 
+    /// Total days from the beginning of the calendar
+    pub fn days(&self) -> u32 {
+        ((self.year - 1) * 12 + (self.month - 1)) * 30 + self.day - 1
+    }
+
     /// Advance time by given minutes
     pub fn advance_by(&mut self, m: f32) -> TimeChanged {
         let mut changed = TimeChanged::default();
@@ -109,3 +114,12 @@ impl Time {
     }
 }
 
+impl TimeChanged {
+    pub fn hour(&self) -> bool {
+        self.hour
+    }
+
+    pub fn day(&self) -> bool {
+        self.day
+    }
+}
@@ -7,6 +7,11 @@ use std::collections::hash_map::{Values, ValuesMut};
 pub struct Town {
     id: String,
     shops: FnvHashMap<u32, Shop>,
+    /// Prosperity grows by trading, and makes shops better
+    prosperity: u32,
+    /// Trading money not converted to prosperity yet
+    #[serde(default)]
+    trading_money: i64,
 }
 
 impl Town {
@@ -14,6 +19,8 @@ impl Town {
         Town {
             id: id.to_owned(),
             shops: FnvHashMap::default(),
+            prosperity: 0,
+            trading_money: 0,
         }
     }
 
@@ -21,6 +28,23 @@ impl Town {
         &self.id
     }
 
+    pub fn prosperity(&self) -> u32 {
+        self.prosperity
+    }
+
+    pub fn add_prosperity(&mut self, v: u32) {
+        self.prosperity = self.prosperity.saturating_add(v);
+    }
+
+    /// Prosperity increases by 1 per money_per_prosperity of trading money.
+    /// The remainder is kept for the next trading.
+    pub fn add_trading_money(&mut self, money: i64, money_per_prosperity: i64) {
+        self.trading_money += money;
+        let v = self.trading_money / money_per_prosperity;
+        self.trading_money -= v * money_per_prosperity;
+        self.add_prosperity(v as u32);
+    }
+
     pub fn get_shop(&self, n: u32) -> Option<&Shop> {
         self.shops.get(&n)
     }
@@ -42,3 +66,19 @@ impl Town {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trading_money_remainder() {
+        let mut town = Town::new("test");
+        town.add_trading_money(60, 100);
+        assert_eq!(town.prosperity(), 0);
+        town.add_trading_money(60, 100);
+        assert_eq!(town.prosperity(), 1);
+        town.add_trading_money(280, 100);
+        assert_eq!(town.prosperity(), 4);
+    }
+}
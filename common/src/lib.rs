@@ -27,5 +27,6 @@ pub mod maptemplate;
 pub mod regiongen;
 pub mod script;
 pub mod sitegen;
+pub mod recipe;
 pub mod piece_pattern;
 
@@ -26,6 +26,9 @@ pub struct MapTemplateObject {
     pub deco: Array2d<Option<u32>>,
     pub boundary: MapTemplateBoundary,
     pub items: Vec<(Vec2d, ItemGen)>,
+    /// Characters on this map by chara template id
+    #[serde(default)]
+    pub charas: Vec<(Vec2d, String)>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
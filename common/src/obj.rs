@@ -22,6 +22,8 @@ pub enum Object {
     RegionGen(RegionGenObject),
     Script(ScriptObject),
     SiteGen(SiteGenObject),
+    Spell(SpellObject),
+    Recipe(RecipeObject),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -102,6 +104,7 @@ pub struct WallObject {
 }
 
 pub use gamedata::item::ItemObject;
+pub use gamedata::spell::SpellObject;
 
 #[derive(Serialize, Deserialize)]
 pub struct Img {
@@ -128,6 +131,7 @@ pub use maptemplate::MapTemplateObject;
 pub use regiongen::RegionGenObject;
 pub use script::ScriptObject;
 pub use sitegen::SiteGenObject;
+pub use recipe::RecipeObject;
 
 macro_rules! impl_object {
     ( $($i:ty),* ) => {
@@ -145,7 +149,7 @@ impl_object!(
     AnimImgObject, CharaTemplateObject, DecoObject, EffectObject,
     ItemObject, SpecialTileObject, TileObject,
     UIImgObject, WallObject,
-    MapTemplateObject, RegionGenObject, SiteGenObject, ScriptObject
+    MapTemplateObject, RegionGenObject, SiteGenObject, ScriptObject, SpellObject, RecipeObject
 );
 
 impl Object {
@@ -164,6 +168,8 @@ impl Object {
             Object::RegionGen(ref o) => &o.id,
             Object::Script(ref o) => &o.id,
             Object::SiteGen(ref o) => &o.id,
+            Object::Spell(ref o) => &o.id,
+            Object::Recipe(ref o) => &o.id,
         }
     }
 }
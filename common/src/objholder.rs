@@ -101,7 +101,7 @@ macro_rules! impl_objholder {
 
         // Index type is an integer type that represents object index in ObjectHolder
         $(
-            #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+            #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
             pub struct $idx(pub u32);
 
             impl_idx!($idx, $obj, $mem);
@@ -122,7 +122,9 @@ impl_objholder! {
     {MapTemplate, MapTemplateObject, map_template, MapTemplateIdx},
     {RegionGen, RegionGenObject, region_gen, RegionGenIdx},
     {Script, ScriptObject, script, ScriptIdx},
-    {SiteGen, SiteGenObject, site_gen, SiteGenIdx}
+    {SiteGen, SiteGenObject, site_gen, SiteGenIdx},
+    {Spell, SpellObject, spell, SpellIdx},
+    {Recipe, RecipeObject, recipe, RecipeIdx}
 }
 
 pub trait ObjectIndex: Sized {
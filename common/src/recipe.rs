@@ -0,0 +1,24 @@
+use gamedata::skill::SkillKind;
+
+/// Recipe to craft items from other items
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecipeObject {
+    pub id: String,
+    /// The skill used to craft
+    pub skill: SkillKind,
+    /// Crafting is difficult if the skill level is lower than this
+    pub difficulty: u16,
+    /// Items consumed by crafting
+    pub inputs: Vec<RecipeInput>,
+    /// Item id of the output
+    pub output: String,
+    pub n_output: u32,
+    /// Id of the deco or special tile needed near the crafter
+    pub workstation: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct RecipeInput {
+    pub id: String,
+    pub n: u32,
+}
@@ -2,6 +2,7 @@
 use std::ops::Index;
 use std::fmt;
 use hashmap::HashMap;
+use gamedata::shop::ServiceKind;
 
 /// Instructions are executed in Game.
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -22,6 +23,8 @@ pub enum Instruction {
     ShopBuy,
     /// Special instruction to start selling at a shop
     ShopSell,
+    /// Special instruction to offer the service at a shop
+    ShopService(ServiceKind),
     /// Special instruction to get locations of dungeons
     GetDungeonLocation,
 }
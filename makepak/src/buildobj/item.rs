@@ -30,6 +30,12 @@ pub fn build_item_object(tomlinput: TomlInput) -> Result<ItemObject, Error> {
         "material" => {
             ItemKind::Material
         }
+        "container" => {
+            ItemKind::Container
+        }
+        "repair_kit" => {
+            ItemKind::RepairKit
+        }
         "special" => {
             ItemKind::Special
         }
@@ -51,6 +51,12 @@ pub fn build_object(tomlinput: TomlInput) -> Result<Object, Error> {
         "site_gen" => {
             return build_site_gen_object(tomlinput).map(|o| Object::SiteGen(o));
         }
+        "spell" => {
+            return build_spell_object(tomlinput).map(|o| Object::Spell(o));
+        }
+        "recipe" => {
+            return build_recipe_object(tomlinput).map(|o| Object::Recipe(o));
+        }
         _ => {
             bail!("Unknown object_type");
         }
@@ -198,3 +204,30 @@ fn build_site_gen_object(tomlinput: TomlInput) -> Result<SiteGenObject, Error> {
     })
 }
 
+fn build_spell_object(tomlinput: TomlInput) -> Result<SpellObject, Error> {
+    let spell = get_optional_field!(tomlinput, spell);
+
+    Ok(SpellObject {
+        id: tomlinput.id,
+        magic_kind: spell.magic_kind,
+        cost: spell.cost,
+        target: spell.target,
+        element: spell.element.unwrap_or(gamedata::Element::None),
+        effect: spell.effect,
+        power: spell.power,
+    })
+}
+
+fn build_recipe_object(tomlinput: TomlInput) -> Result<RecipeObject, Error> {
+    let recipe = get_optional_field!(tomlinput, recipe);
+
+    Ok(RecipeObject {
+        id: tomlinput.id,
+        skill: recipe.skill,
+        difficulty: recipe.difficulty,
+        inputs: recipe.inputs,
+        output: recipe.output,
+        n_output: recipe.n_output.unwrap_or(1),
+        workstation: recipe.workstation,
+    })
+}
@@ -4,6 +4,7 @@ use nom::types::CompleteStr;
 use common::hashmap::HashMap;
 
 use common::script::*;
+use common::gamedata::ServiceKind;
 use error::PakCompileError;
 use super::expr_parser::*;
 
@@ -110,8 +111,35 @@ fn shop_instruction_test() {
         CompleteStr("shop_buy()\n")), Ok((CompleteStr(""), Instruction::ShopBuy)));
     assert_eq!(shop_sell_instruction(
         CompleteStr("shop_sell()\n")), Ok((CompleteStr(""), Instruction::ShopSell)));
+    assert_eq!(shop_service_instruction(
+        CompleteStr("shop_service(identify)\n")),
+        Ok((CompleteStr(""), Instruction::ShopService(ServiceKind::Identify))));
+    assert_eq!(shop_service_instruction(
+        CompleteStr("shop_service( inn )\n")),
+        Ok((CompleteStr(""), Instruction::ShopService(ServiceKind::Inn))));
+    assert!(shop_service_instruction(CompleteStr("shop_service(unknown)\n")).is_err());
 }
 
+fn service_kind(s: String) -> Option<ServiceKind> {
+    Some(match s.as_ref() {
+        "heal" => ServiceKind::Heal,
+        "identify" => ServiceKind::Identify,
+        "repair" => ServiceKind::Repair,
+        "storage" => ServiceKind::Storage,
+        "inn" => ServiceKind::Inn,
+        _ => { return None; }
+    })
+}
+
+named!(shop_service_instruction<CompleteStr, Instruction>,
+    do_parse!(
+        ws!(tag!("shop_service")) >>
+        kind: map_opt!(delimited!(char!('('), ws!(id), char!(')')), service_kind) >>
+        end_line >>
+        (Instruction::ShopService(kind))
+    )
+);
+
 named!(talk_instruction<CompleteStr, Instruction>,
     do_parse!(
         ws!(tag!("talk")) >>
@@ -187,6 +215,7 @@ named!(instruction<CompleteStr, Instruction>,
         remove_item_instruction |
         shop_buy_instruction |
         shop_sell_instruction |
+        shop_service_instruction |
         get_dungeon_location_instruction
     )
 );
@@ -16,6 +16,8 @@ pub struct TomlInput {
     pub region_gen: Option<RegionGenDepInput>,
     pub script: Option<ScriptDepInput>,
     pub site_gen: Option<SiteGenDepInput>,
+    pub spell: Option<SpellDepInput>,
+    pub recipe: Option<RecipeDepInput>,
 }
 
 /// If tomlinput has specified optional field, return it. If not, return error.
@@ -130,3 +132,24 @@ pub struct ScriptDepInput {
     pub script: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpellDepInput {
+    pub magic_kind: gamedata::MagicKind,
+    pub cost: i32,
+    pub target: gamedata::SpellTarget,
+    pub element: Option<gamedata::Element>,
+    pub effect: gamedata::SpellEffect,
+    pub power: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeDepInput {
+    pub skill: gamedata::SkillKind,
+    pub difficulty: u16,
+    pub inputs: Vec<::common::recipe::RecipeInput>,
+    pub output: String,
+    pub n_output: Option<u32>,
+    pub workstation: Option<String>,
+}
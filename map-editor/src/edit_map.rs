@@ -15,6 +15,8 @@ pub struct EditingMap {
     pub wall: Array2d<WallIdxPP>,
     pub deco: Array2d<Option<DecoIdx>>,
     pub items: Array2d<Vec<ItemGen>>,
+    /// Characters by chara template id
+    pub charas: Vec<(Vec2d, String)>,
 }
 
 impl EditingMap {
@@ -24,7 +26,8 @@ impl EditingMap {
         let deco = Array2d::new(width, height, None);
         let property = MapProperty::new(id);
         let items = Array2d::new(width, height, vec![]);
-        EditingMap { property, width, height, tile, wall, deco, items }
+        let charas = Vec::new();
+        EditingMap { property, width, height, tile, wall, deco, items, charas }
     }
 
     pub fn set_tile(&mut self, pos: Vec2d, idx: TileIdx, layer: usize) {
@@ -74,6 +77,18 @@ impl EditingMap {
         self.items[pos].get(0)
     }
 
+    pub fn set_chara(&mut self, pos: Vec2d, chara_id: Option<String>) {
+        self.charas.retain(|&(p, _)| p != pos);
+        if let Some(chara_id) = chara_id {
+            self.charas.push((pos, chara_id));
+            self.charas.sort();
+        }
+    }
+
+    pub fn get_chara(&self, pos: Vec2d) -> Option<&str> {
+        self.charas.iter().find(|&&(p, _)| p == pos).map(|&(_, ref id)| id.as_str())
+    }
+
     pub fn erase(&mut self, pos: Vec2d) {
         self.wall[pos] = WallIdxPP::default();
         self.deco[pos] = None;
@@ -115,6 +130,7 @@ impl EditingMap {
         self.wall = wall;
         let deco = self.deco.clip_with_default((0, 0), (new_w, new_h), None);
         self.deco = deco;
+        self.charas.retain(|&(pos, _)| pos.0 < new_w as i32 && pos.1 < new_h as i32);
     }
 
     pub fn create_mapobj(&self) -> MapTemplateObject {
@@ -193,6 +209,7 @@ impl EditingMap {
             deco: deco_map,
             boundary: self.property.boundary,
             items,
+            charas: self.charas.clone(),
         }
     }
 }
@@ -237,6 +254,7 @@ impl From<MapTemplateObject> for EditingMap {
             map.set_item(*pos, Some(item_gen.clone()));
         }
 
+        map.charas = obj.charas;
         map.property.boundary = obj.boundary;
         
         map
@@ -31,6 +31,7 @@ pub struct PropertyControls {
     pub button_select_tile_mode: gtk::Button,
     pub label_selected_tile: gtk::Label,
     pub entry_item_id:     gtk::Entry,
+    pub entry_chara_id:    gtk::Entry,
     pub selected_tile: Rc<Cell<Vec2d>>,
 }
 
@@ -58,6 +59,7 @@ impl PropertyControls {
             button_select_tile_mode: get_object!(builder, "button-select-tile-mode"),
             label_selected_tile: get_object!(builder, "label-selected-tile"),
             entry_item_id:     get_object!(builder, "entry-item-id"),
+            entry_chara_id:    get_object!(builder, "entry-chara-id"),
             selected_tile: Rc::new(Cell::new(Vec2d(0, 0))),
         }
     }
@@ -94,6 +96,11 @@ impl PropertyControls {
         } else {
             self.entry_item_id.set_text("");
         }
+        if let Some(chara_id) = map.get_chara(self.selected_tile.get()) {
+            self.entry_chara_id.set_text(chara_id);
+        } else {
+            self.entry_chara_id.set_text("");
+        }
     }
 }
 
@@ -237,4 +244,16 @@ fn connect_for_tile_edit_controls(ui: &Ui) {
             uic.map.borrow_mut().set_item(uic.property_controls.selected_tile.get(), item_gen);
         }
     });
+    let uic = ui.clone();
+    ui.property_controls.entry_chara_id.connect_changed(move |widget| {
+        if uic.get_signal_mode() {
+            let text = widget.get_text();
+            let chara_id = if text.is_none() || text.as_ref().unwrap() == ""  {
+                None
+            } else {
+                Some(text.unwrap().to_owned())
+            };
+            uic.map.borrow_mut().set_chara(uic.property_controls.selected_tile.get(), chara_id);
+        }
+    });
 }
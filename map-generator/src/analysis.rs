@@ -0,0 +1,102 @@
+
+//! Analyze generated map layouts to find corridors, dead ends and chokepoints
+
+use std::cmp::min;
+use array2d::*;
+use super::GeneratedMap;
+
+const FOUR_DIRS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+pub fn analyze(gm: &mut GeneratedMap) {
+    gm.corridors = find_corridors(gm);
+    gm.dead_ends = find_dead_ends(gm);
+    gm.chokepoints = find_chokepoints(gm);
+}
+
+fn is_passable(gm: &GeneratedMap, p: Vec2d) -> bool {
+    gm.tile.in_range(p) && gm.tile[p].is_passable()
+}
+
+fn n_passable_neighbors(gm: &GeneratedMap, p: Vec2d) -> usize {
+    FOUR_DIRS.iter().filter(|d| is_passable(gm, p + **d)).count()
+}
+
+/// If the map has rooms, passable tiles outside of rooms are corridors.
+/// Otherwise, passable tiles in one tile width passages are corridors.
+fn find_corridors(gm: &GeneratedMap) -> Vec<Vec2d> {
+    gm.tile.iter_idx()
+        .filter(|&p| gm.tile[p].is_passable())
+        .filter(|&p| {
+            if gm.rooms.is_empty() {
+                let ns = is_passable(gm, p + (0, -1)) && is_passable(gm, p + (0, 1));
+                let ew = is_passable(gm, p + (-1, 0)) && is_passable(gm, p + (1, 0));
+                n_passable_neighbors(gm, p) == 2 && (ns || ew)
+            } else {
+                !gm.rooms.iter().any(|room| room.contains(p))
+            }
+        })
+        .collect()
+}
+
+/// Dead ends are passable tiles which have only one passable neighbor
+fn find_dead_ends(gm: &GeneratedMap) -> Vec<Vec2d> {
+    gm.tile.iter_idx()
+        .filter(|&p| gm.tile[p].is_passable() && n_passable_neighbors(gm, p) == 1)
+        .collect()
+}
+
+/// Chokepoints are the tiles which separate the passable region if they are blocked.
+/// They are found as articulation points by depth first search.
+fn find_chokepoints(gm: &GeneratedMap) -> Vec<Vec2d> {
+    let (w, h) = gm.tile.size();
+    // Visiting order. 0 means not visited.
+    let mut order = Array2d::new(w, h, 0u32);
+    let mut low = Array2d::new(w, h, 0u32);
+    let mut is_chokepoint = Array2d::new(w, h, false);
+    let mut count = 1;
+
+    for root in gm.tile.iter_idx() {
+        if order[root] != 0 || !gm.tile[root].is_passable() { continue; }
+
+        order[root] = count;
+        low[root] = count;
+        count += 1;
+        let mut n_root_children = 0;
+        // (Position, parent, the index of next direction to search)
+        let mut stack: Vec<(Vec2d, Option<Vec2d>, usize)> = vec![(root, None, 0)];
+
+        while let Some(&(p, parent, i)) = stack.last() {
+            if i < FOUR_DIRS.len() {
+                stack.last_mut().unwrap().2 += 1;
+                let next = p + FOUR_DIRS[i];
+                if !is_passable(gm, next) { continue; }
+
+                if order[next] == 0 {
+                    order[next] = count;
+                    low[next] = count;
+                    count += 1;
+                    if p == root {
+                        n_root_children += 1;
+                    }
+                    stack.push((next, Some(p), 0));
+                } else if Some(next) != parent {
+                    low[p] = min(low[p], order[next]);
+                }
+            } else {
+                stack.pop();
+                if let Some(parent) = parent {
+                    low[parent] = min(low[parent], low[p]);
+                    if parent != root && low[p] >= order[parent] {
+                        is_chokepoint[parent] = true;
+                    }
+                }
+            }
+        }
+
+        if n_root_children >= 2 {
+            is_chokepoint[root] = true;
+        }
+    }
+
+    gm.tile.iter_idx().filter(|&p| is_chokepoint[p]).collect()
+}
@@ -0,0 +1,139 @@
+
+use std::cmp::{min, max};
+use array2d::*;
+use rng::*;
+use super::{GeneratedMap, TileKind, Room};
+
+/// Parameters for binary space partition
+struct BspParams {
+    room_size_min: i32,
+    room_size_max: i32,
+    corridor_width: i32,
+}
+
+pub fn write_to_map(
+    gm: &mut GeneratedMap, room_size_min: u32, room_size_max: u32, corridor_width: u32) {
+
+    let params = BspParams {
+        room_size_min: max(room_size_min, 1) as i32,
+        room_size_max: max(room_size_max, room_size_min) as i32,
+        corridor_width: max(corridor_width, 1) as i32,
+    };
+
+    for p in gm.tile.iter_idx() {
+        gm.tile[p] = TileKind::Wall;
+    }
+
+    let right_bottom = Vec2d(gm.size.0 - 1, gm.size.1 - 1);
+    let mut rooms = Vec::new();
+    split(gm, &params, &mut rooms, Vec2d(0, 0), right_bottom);
+
+    // Set entrance/exit at the centers of different rooms
+    if rooms.is_empty() {
+        return;
+    }
+    let start = gen_range(0, rooms.len());
+    let end = if rooms.len() > 1 {
+        (start + gen_range(1, rooms.len())) % rooms.len()
+    } else {
+        start
+    };
+    gm.entrance = rooms[start].center();
+    if start != end {
+        gm.exit = Some(rooms[end].center());
+    }
+    gm.rooms = rooms;
+}
+
+/// Split the given area recursively, and create a room for each leaf.
+/// Rooms in the separated two areas are connected by a corridor.
+/// Returns the indices of rooms in this area.
+fn split(gm: &mut GeneratedMap, params: &BspParams, rooms: &mut Vec<Room>,
+         top_left: Vec2d, right_bottom: Vec2d) -> Vec<usize> {
+
+    let w = right_bottom.0 - top_left.0 + 1;
+    let h = right_bottom.1 - top_left.1 + 1;
+    // Rooms need one tile margin to both sides
+    let min_area_len = params.room_size_min + 2;
+    let max_area_len = params.room_size_max + 2;
+
+    let can_split_x = w >= min_area_len * 2;
+    let can_split_y = h >= min_area_len * 2;
+    let split_x = if w > max_area_len && can_split_x && (w >= h || !can_split_y) {
+        Some(true)
+    } else if h > max_area_len && can_split_y {
+        Some(false)
+    } else {
+        None
+    };
+
+    let (a, b) = match split_x {
+        Some(true) => {
+            let x = top_left.0 + gen_range(min_area_len, w - min_area_len + 1);
+            (split(gm, params, rooms, top_left, Vec2d(x - 1, right_bottom.1)),
+             split(gm, params, rooms, Vec2d(x, top_left.1), right_bottom))
+        }
+        Some(false) => {
+            let y = top_left.1 + gen_range(min_area_len, h - min_area_len + 1);
+            (split(gm, params, rooms, top_left, Vec2d(right_bottom.0, y - 1)),
+             split(gm, params, rooms, Vec2d(top_left.0, y), right_bottom))
+        }
+        None => {
+            return create_room(gm, params, rooms, top_left, w, h).into_iter().collect();
+        }
+    };
+
+    if let (Some(&room_a), Some(&room_b)) = (get_rng().choose(&a), get_rng().choose(&b)) {
+        let start = rooms[room_a].center();
+        let end = rooms[room_b].center();
+        dig_corridor(gm, params.corridor_width, start, end);
+    }
+
+    let mut result = a;
+    result.extend(b);
+    result
+}
+
+/// Create a room in the given area
+fn create_room(gm: &mut GeneratedMap, params: &BspParams, rooms: &mut Vec<Room>,
+               top_left: Vec2d, w: i32, h: i32) -> Option<usize> {
+
+    let room_w_max = min(params.room_size_max, w - 2);
+    let room_h_max = min(params.room_size_max, h - 2);
+    if room_w_max < 1 || room_h_max < 1 {
+        return None;
+    }
+    let room_w = gen_range(min(params.room_size_min, room_w_max), room_w_max + 1);
+    let room_h = gen_range(min(params.room_size_min, room_h_max), room_h_max + 1);
+    let x = top_left.0 + 1 + gen_range(0, w - 2 - room_w + 1);
+    let y = top_left.1 + 1 + gen_range(0, h - 2 - room_h + 1);
+
+    let room = Room {
+        top_left: Vec2d(x, y),
+        right_bottom: Vec2d(x + room_w - 1, y + room_h - 1),
+    };
+    for p in room.iter() {
+        gm.tile[p] = TileKind::Floor;
+    }
+    rooms.push(room);
+    Some(rooms.len() - 1)
+}
+
+/// Dig L-shaped corridor between two points
+fn dig_corridor(gm: &mut GeneratedMap, width: i32, start: Vec2d, end: Vec2d) {
+    let corner = if gen_range(0, 2) == 0 {
+        Vec2d(end.0, start.1)
+    } else {
+        Vec2d(start.0, end.1)
+    };
+
+    for p in LineIter::new(start, corner).chain(LineIter::new(corner, end)) {
+        // Map edges are kept as walls
+        let top_left = Vec2d(max(p.0, 1), max(p.1, 1));
+        let right_bottom = Vec2d(
+            min(p.0 + width - 1, gm.size.0 - 2), min(p.1 + width - 1, gm.size.1 - 2));
+        for q in RectIter::new(top_left, right_bottom) {
+            gm.tile[q] = TileKind::Floor;
+        }
+    }
+}
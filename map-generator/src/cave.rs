@@ -0,0 +1,67 @@
+
+use array2d::*;
+use rng::*;
+use super::{GeneratedMap, TileKind};
+use verify;
+
+pub fn write_to_map(gm: &mut GeneratedMap, fill_ratio: f64, n_smoothing: u32) {
+    // Fill at random. Edges are always walls.
+    for p in gm.tile.iter_idx() {
+        gm.tile[p] = if is_edge(gm.size, p) || get_rng().gen_bool(fill_ratio) {
+            TileKind::Wall
+        } else {
+            TileKind::Floor
+        };
+    }
+
+    for _ in 0..n_smoothing {
+        smooth(gm);
+    }
+
+    connect_regions(gm);
+
+    // Determine entrance and exit
+    let floor_tiles: Vec<Vec2d> = gm.tile.iter_idx().filter(|&p| gm.tile[p] == TileKind::Floor).collect();
+    gm.entrance = *get_rng().choose(&floor_tiles).unwrap();
+    if floor_tiles.len() > 1 {
+        loop {
+            let end = *get_rng().choose(&floor_tiles).unwrap();
+            if end != gm.entrance {
+                gm.exit = Some(end);
+                break;
+            }
+        }
+    }
+}
+
+fn is_edge(size: Vec2d, p: Vec2d) -> bool {
+    p.0 == 0 || p.1 == 0 || p.0 == size.0 - 1 || p.1 == size.1 - 1
+}
+
+/// One step of the cellular automaton.
+/// A tile becomes wall if five or more tiles in its 3x3 neighborhood are walls.
+fn smooth(gm: &mut GeneratedMap) {
+    let mut new_tile = gm.tile.clone();
+
+    for p in gm.tile.iter_idx() {
+        if is_edge(gm.size, p) { continue; }
+
+        let n_wall = RectIter::new(p + (-1, -1), p + (1, 1))
+            .filter(|&q| gm.tile[q] == TileKind::Wall)
+            .count();
+        new_tile[p] = if n_wall >= 5 { TileKind::Wall } else { TileKind::Floor };
+    }
+
+    gm.tile = new_tile;
+}
+
+/// Connect separated floor regions by tunnels
+fn connect_regions(gm: &mut GeneratedMap) {
+    if verify::split_regions(gm).is_empty() {
+        // All tiles became walls. Open the center tile at least.
+        let center = Vec2d(gm.size.0 / 2, gm.size.1 / 2);
+        gm.tile[center] = TileKind::Floor;
+        return;
+    }
+    verify::connect_regions(gm);
+}
@@ -55,13 +55,25 @@ pub fn create_fractal(size: Vec2d) -> Array2d<f32> {
         write_rect(&mut map, *b, Vec2d(i, i), Vec2d(size.0 - i - 1, size.1 - i - 1));
     }
 
-    write_block(&mut map, 8, 1.0);
-    write_block(&mut map, 7, 1.0);
-    write_block(&mut map, 6, 1.0);
-    write_block(&mut map, 5, 1.0);
-    write_block(&mut map, 4, 1.0);
-    write_block(&mut map, 2, 1.0);
-    write_block(&mut map, 1, 1.0);
+    write_blocks_and_normalize(&mut map);
+    map
+}
+
+/// Fractal noise normalized to [0, 1] without biasing for edges
+pub fn create_noise(size: Vec2d) -> Array2d<f32> {
+    let mut map = Array2d::new(size.0 as u32, size.1 as u32, 0.0);
+    write_blocks_and_normalize(&mut map);
+    map
+}
+
+fn write_blocks_and_normalize(map: &mut Array2d<f32>) {
+    write_block(map, 8, 1.0);
+    write_block(map, 7, 1.0);
+    write_block(map, 6, 1.0);
+    write_block(map, 5, 1.0);
+    write_block(map, 4, 1.0);
+    write_block(map, 2, 1.0);
+    write_block(map, 1, 1.0);
 
     // Normalization
     let mut max = 0.0;
@@ -76,11 +88,11 @@ pub fn create_fractal(size: Vec2d) -> Array2d<f32> {
             min = map[p];
         }
     }
-    for p in map.iter_idx() {
-        map[p] = (map[p] - min) / (max - min);
+    if max > min {
+        for p in map.iter_idx() {
+            map[p] = (map[p] - min) / (max - min);
+        }
     }
-    
-    map
 }
 
 fn write_block(map: &mut Array2d<f32>, block_size: u32, weight: f32) {
@@ -8,6 +8,10 @@ use array2d::*;
 
 mod lattice;
 mod fractal;
+mod bsp;
+mod cave;
+mod analysis;
+mod verify;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum TileKind {
@@ -23,11 +27,86 @@ impl TileKind {
     }
 }
 
+/// The maximum number of trials to generate a valid map
+const MAX_GENERATE_TRY: usize = 100;
+
 pub struct GeneratedMap {
     pub size: Vec2d,
     pub tile: Array2d<TileKind>,
     pub entrance: Vec2d,
     pub exit: Option<Vec2d>,
+    /// Rooms in this map. Empty if the generator does not create rooms.
+    pub rooms: Vec<Room>,
+    /// Passable tiles which are not in rooms, or in narrow passages
+    pub corridors: Vec<Vec2d>,
+    /// Passable tiles which have only one passable neighbor
+    pub dead_ends: Vec<Vec2d>,
+    /// Passable tiles which separate the map if blocked
+    pub chokepoints: Vec<Vec2d>,
+}
+
+impl GeneratedMap {
+    fn new(size: Vec2d) -> GeneratedMap {
+        GeneratedMap {
+            size, tile: Array2d::new(size.0 as u32, size.1 as u32, TileKind::Floor),
+            entrance: Vec2d(0, 0),
+            exit: None,
+            rooms: Vec::new(),
+            corridors: Vec::new(),
+            dead_ends: Vec::new(),
+            chokepoints: Vec::new(),
+        }
+    }
+
+    /// All passable tiles are reachable from the entrance
+    pub fn is_connected(&self) -> bool {
+        verify::is_connected(self)
+    }
+
+    /// Passable tiles adjacent to the room from outside
+    pub fn room_exits(&self, room: &Room) -> Vec<Vec2d> {
+        let top_left = room.top_left + (-1, -1);
+        let right_bottom = room.right_bottom + (1, 1);
+        RectIter::new(top_left, right_bottom)
+            .filter(|&p| !room.contains(p))
+            .filter(|&p| {
+                // Skip corners
+                (p.0 != top_left.0 && p.0 != right_bottom.0) || (p.1 != top_left.1 && p.1 != right_bottom.1)
+            })
+            .filter(|&p| self.tile.in_range(p) && self.tile[p].is_passable())
+            .collect()
+    }
+}
+
+/// Rectangle room in generated map
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Room {
+    pub top_left: Vec2d,
+    pub right_bottom: Vec2d,
+}
+
+impl Room {
+    pub fn w(&self) -> i32 {
+        self.right_bottom.0 - self.top_left.0 + 1
+    }
+
+    pub fn h(&self) -> i32 {
+        self.right_bottom.1 - self.top_left.1 + 1
+    }
+
+    pub fn center(&self) -> Vec2d {
+        Vec2d((self.top_left.0 + self.right_bottom.0) / 2, (self.top_left.1 + self.right_bottom.1) / 2)
+    }
+
+    pub fn contains(&self, p: Vec2d) -> bool {
+        self.top_left.0 <= p.0 && p.0 <= self.right_bottom.0 &&
+            self.top_left.1 <= p.1 && p.1 <= self.right_bottom.1
+    }
+
+    /// Iterate tiles in this room
+    pub fn iter(&self) -> RectIter {
+        RectIter::new(self.top_left, self.right_bottom)
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -35,6 +114,19 @@ enum MapGenParam {
     Flat,
     Lattice { nx: u32, ny: u32, step_min: u32, step_max: u32, door_weight: f64 },
     Fractal,
+    Bsp { room_size_min: u32, room_size_max: u32, corridor_width: u32 },
+    Cave { fill_ratio: f64, n_smoothing: u32 },
+}
+
+/// Create smooth noise whose values are in [0, 1].
+/// It is useful for blending terrains.
+pub fn create_noise<S: Into<Vec2d>>(size: S) -> Array2d<f32> {
+    fractal::create_noise(size.into())
+}
+
+/// Create smooth noise whose values are in [0, 1] and high at edges
+pub fn create_edge_biased_noise<S: Into<Vec2d>>(size: S) -> Array2d<f32> {
+    fractal::create_fractal(size.into())
 }
 
 pub struct MapGenerator {
@@ -44,14 +136,8 @@ pub struct MapGenerator {
 
 impl MapGenerator {
     pub fn new<S: Into<Vec2d>>(size: S) -> MapGenerator {
-        let size = size.into();
-        let map = GeneratedMap {
-            size, tile: Array2d::new(size.0 as u32, size.1 as u32, TileKind::Floor),
-            entrance: Vec2d(0, 0),
-            exit: None,
-        };
         MapGenerator {
-            map,
+            map: GeneratedMap::new(size.into()),
             genparam: None,
         }
     }
@@ -80,22 +166,65 @@ impl MapGenerator {
         mg
     }
 
-    /// Generate one map
-    pub fn generate(mut self) -> GeneratedMap {
-        match self.genparam.expect("Map generate before giving parameters") {
-            MapGenParam::Flat => {
-                return self.map;
-            },
-            MapGenParam::Lattice { nx, ny, step_min, step_max, door_weight } => {
-                let lattice = lattice::create_lattice(nx, ny, step_min, step_max);
-                lattice.write_to_map(&mut self.map, door_weight);
-                return self.map;
-            },
-            MapGenParam::Fractal => {
-                fractal::write_to_map(&mut self.map);
-                return self.map;
-            },
+    /// Create rooms and corridors map by binary space partition
+    pub fn bsp(self, room_size_min: u32, room_size_max: u32, corridor_width: u32) -> MapGenerator {
+
+        let mut mg = self;
+        mg.genparam = Some(MapGenParam::Bsp { room_size_min, room_size_max, corridor_width });
+        mg
+    }
+
+    /// Create cave map by cellular automata.
+    /// fill_ratio is the initial ratio of walls, and separated regions are connected by tunnels.
+    /// fill_ratio is clamped to [0, 1].
+    pub fn cave(self, fill_ratio: f64, n_smoothing: u32) -> MapGenerator {
+
+        let fill_ratio = if fill_ratio.is_nan() { 0.0 } else { fill_ratio.max(0.0).min(1.0) };
+        let mut mg = self;
+        mg.genparam = Some(MapGenParam::Cave { fill_ratio, n_smoothing });
+        mg
+    }
+
+    /// Generate one map.
+    /// Separated regions are connected, and the map is regenerated if stairs are invalid.
+    pub fn generate(self) -> GeneratedMap {
+        let genparam = self.genparam.expect("Map generate before giving parameters");
+        let size = self.map.size;
+        let mut map = self.map;
+
+        for i in 0..MAX_GENERATE_TRY {
+            write_to_map(&mut map, &genparam);
+            if verify::verify_and_repair(&mut map) {
+                break;
+            }
+            if i == MAX_GENERATE_TRY - 1 {
+                // Never return a broken map
+                verify::force_repair(&mut map);
+            } else {
+                map = GeneratedMap::new(size);
+            }
         }
+        analysis::analyze(&mut map);
+        map
+    }
+}
+
+fn write_to_map(map: &mut GeneratedMap, genparam: &MapGenParam) {
+    match *genparam {
+        MapGenParam::Flat => (),
+        MapGenParam::Lattice { nx, ny, step_min, step_max, door_weight } => {
+            let lattice = lattice::create_lattice(nx, ny, step_min, step_max);
+            lattice.write_to_map(map, door_weight);
+        },
+        MapGenParam::Fractal => {
+            fractal::write_to_map(map);
+        },
+        MapGenParam::Bsp { room_size_min, room_size_max, corridor_width } => {
+            bsp::write_to_map(map, room_size_min, room_size_max, corridor_width);
+        },
+        MapGenParam::Cave { fill_ratio, n_smoothing } => {
+            cave::write_to_map(map, fill_ratio, n_smoothing);
+        },
     }
 }
 
@@ -127,6 +256,12 @@ impl std::fmt::Display for GeneratedMap {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn noise_is_normalized() {
+        let noise = create_noise((20, 20));
+        assert!(noise.iter().all(|v| 0.0 <= *v && *v <= 1.0));
+    }
+
     #[test]
     fn flat_map() {
         let map = MapGenerator::new((10, 10)).flat().generate();
@@ -149,4 +284,126 @@ mod tests {
         let map = MapGenerator::new((30, 30)).fractal().generate();
         println!("{}", map);
     }
+
+    #[test]
+    fn bsp_map() {
+        println!("BSP map:");
+        let map = MapGenerator::new((40, 30)).bsp(3, 8, 1).generate();
+        println!("{}", map);
+
+        assert!(!map.rooms.is_empty());
+        for room in &map.rooms {
+            assert!(3 <= room.w() && room.w() <= 8);
+            assert!(3 <= room.h() && room.h() <= 8);
+            for p in room.iter() {
+                assert_eq!(map.tile[p], TileKind::Floor);
+            }
+        }
+    }
+
+    #[test]
+    fn cave_map() {
+        println!("Cave map:");
+        let map = MapGenerator::new((40, 30)).cave(0.45, 4).generate();
+        println!("{}", map);
+
+        assert_eq!(map.tile[map.entrance], TileKind::Floor);
+        assert_eq!(map.tile[map.exit.unwrap()], TileKind::Floor);
+    }
+
+    #[test]
+    fn force_repair() {
+        // Separated regions and stairs on walls
+        let mut map = MapGenerator::new((7, 3)).flat().generate();
+        for p in map.tile.iter_idx() {
+            if p.1 != 1 || p.0 == 3 {
+                map.tile[p] = TileKind::Wall;
+            }
+        }
+        map.entrance = Vec2d(1, 0);
+        map.exit = Some(Vec2d(10, 10));
+        assert!(!verify::is_connected(&map));
+
+        verify::force_repair(&mut map);
+        assert!(verify::verify_and_repair(&mut map));
+        assert_eq!(map.tile[map.entrance], TileKind::Floor);
+        assert!(map.tile[map.exit.unwrap()].is_passable());
+    }
+
+    #[test]
+    fn connect_regions() {
+        // Isolated floor tiles in a grid
+        let mut map = MapGenerator::new((9, 9)).flat().generate();
+        for p in map.tile.iter_idx() {
+            if p.0 % 4 != 0 || p.1 % 4 != 0 {
+                map.tile[p] = TileKind::Wall;
+            }
+        }
+        map.entrance = Vec2d(0, 0);
+        map.exit = Some(Vec2d(8, 8));
+        assert_eq!(verify::split_regions(&map).len(), 9);
+
+        verify::connect_regions(&mut map);
+        assert!(verify::is_connected(&map));
+        // Neighbouring regions are connected by the shortest tunnels
+        let n_floor = map.tile.iter().filter(|t| t.is_passable()).count();
+        assert_eq!(n_floor, 9 + 8 * 3);
+    }
+
+    #[test]
+    fn map_analysis() {
+        // Two rooms connected by one passage
+        let mut map = MapGenerator::new((7, 3)).flat().generate();
+        for p in map.tile.iter_idx() {
+            if p.1 != 1 || p.0 == 0 || p.0 == 6 {
+                map.tile[p] = TileKind::Wall;
+            }
+        }
+        analysis::analyze(&mut map);
+
+        assert_eq!(map.dead_ends, vec![Vec2d(1, 1), Vec2d(5, 1)]);
+        assert_eq!(map.chokepoints, vec![Vec2d(2, 1), Vec2d(3, 1), Vec2d(4, 1)]);
+        assert_eq!(map.corridors, vec![Vec2d(2, 1), Vec2d(3, 1), Vec2d(4, 1)]);
+    }
+
+    const N_SEED: u64 = 2000;
+
+    /// Generated maps must be connected, and stairs must be on passable tiles in the map
+    fn check_generated_maps<F: Fn() -> MapGenerator>(f: F) {
+        for seed in 0..N_SEED {
+            rng::reseed_with(seed);
+            let map = f().generate();
+
+            assert!(map.tile.in_range(map.entrance) && map.tile[map.entrance].is_passable(),
+                    "Invalid entrance with seed {}:\n{}", seed, map);
+            if let Some(exit) = map.exit {
+                assert!(map.tile.in_range(exit) && map.tile[exit].is_passable(),
+                        "Invalid exit with seed {}:\n{}", seed, map);
+            }
+            assert!(map.is_connected(), "Disconnected map with seed {}:\n{}", seed, map);
+        }
+    }
+
+    #[test]
+    fn lattice_connectivity() {
+        check_generated_maps(|| MapGenerator::new((19, 15)).lattice(5, 4, 3, 7, 0.5));
+        check_generated_maps(|| MapGenerator::new((40, 40)).lattice(6, 6, 3, 20, 0.8));
+    }
+
+    #[test]
+    fn fractal_connectivity() {
+        check_generated_maps(|| MapGenerator::new((30, 30)).fractal());
+    }
+
+    #[test]
+    fn bsp_connectivity() {
+        check_generated_maps(|| MapGenerator::new((40, 40)).bsp(3, 7, 1));
+        check_generated_maps(|| MapGenerator::new((30, 20)).bsp(2, 10, 2));
+    }
+
+    #[test]
+    fn cave_connectivity() {
+        check_generated_maps(|| MapGenerator::new((40, 40)).cave(0.45, 4));
+        check_generated_maps(|| MapGenerator::new((20, 20)).cave(0.6, 2));
+    }
 }
@@ -0,0 +1,174 @@
+
+//! Verify that generated maps are playable, and repair them if possible
+
+use std::collections::VecDeque;
+use array2d::*;
+use rng::*;
+use super::{GeneratedMap, TileKind};
+
+const FOUR_DIRS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Check the map and connect separated regions.
+/// Returns false if the map cannot be repaired and should be regenerated.
+pub fn verify_and_repair(gm: &mut GeneratedMap) -> bool {
+    if !is_valid_stairs(gm, gm.entrance) {
+        return false;
+    }
+    if let Some(exit) = gm.exit {
+        if !is_valid_stairs(gm, exit) {
+            return false;
+        }
+    }
+
+    connect_regions(gm);
+    is_connected(gm)
+}
+
+/// Repair the map forcibly. Used when regenerating the map fails many times.
+/// Stairs on impassable tiles are dug, and all regions are connected.
+pub fn force_repair(gm: &mut GeneratedMap) {
+    if split_regions(gm).is_empty() {
+        let center = Vec2d(gm.size.0 / 2, gm.size.1 / 2);
+        gm.tile[center] = TileKind::Floor;
+    }
+    gm.entrance = repair_stairs(gm, gm.entrance);
+    if let Some(exit) = gm.exit {
+        gm.exit = Some(repair_stairs(gm, exit));
+    }
+    connect_regions(gm);
+}
+
+/// Make the stairs tile passable. Stairs outside the map are moved to a passable tile.
+fn repair_stairs(gm: &mut GeneratedMap, p: Vec2d) -> Vec2d {
+    if !gm.tile.in_range(p) {
+        let passable: Vec<Vec2d> = gm.tile.iter_idx().filter(|&q| gm.tile[q].is_passable()).collect();
+        return *get_rng().choose(&passable).unwrap();
+    }
+    if !gm.tile[p].is_passable() {
+        gm.tile[p] = TileKind::Floor;
+    }
+    p
+}
+
+/// Stairs must be on passable tiles inside the map
+fn is_valid_stairs(gm: &GeneratedMap, p: Vec2d) -> bool {
+    gm.tile.in_range(p) && gm.tile[p].is_passable()
+}
+
+/// All passable tiles are reachable from the entrance
+pub fn is_connected(gm: &GeneratedMap) -> bool {
+    if !is_valid_stairs(gm, gm.entrance) {
+        return false;
+    }
+    let regions = split_regions(gm);
+    regions.len() == 1
+}
+
+/// Dig tunnels between separated passable regions until all regions are connected.
+/// All regions are grown over walls at the same time, and pairs of regions meeting there
+/// are connected by the minimum spanning tree of the tunnel lengths.
+pub fn connect_regions(gm: &mut GeneratedMap) {
+    let regions = split_regions(gm);
+    if regions.len() <= 1 {
+        return;
+    }
+
+    // The nearest region, its tile and the distance from it for each tile
+    let mut nearest: Array2d<Option<(usize, Vec2d, i32)>> =
+        Array2d::new(gm.size.0 as u32, gm.size.1 as u32, None);
+    let mut queue = VecDeque::new();
+    for (i, region) in regions.iter().enumerate() {
+        for &p in region {
+            nearest[p] = Some((i, p, 0));
+            queue.push_back(p);
+        }
+    }
+
+    // Tunnels between neighbouring regions as (length, region a, region b, start, end)
+    let mut tunnels = Vec::new();
+    while let Some(p) = queue.pop_front() {
+        let (i, start, d) = nearest[p].unwrap();
+        for dir in &FOUR_DIRS {
+            let next = p + *dir;
+            if !gm.tile.in_range(next) { continue; }
+            match nearest[next] {
+                None => {
+                    nearest[next] = Some((i, start, d + 1));
+                    queue.push_back(next);
+                }
+                Some((j, end, d_next)) if i < j => {
+                    tunnels.push((d + d_next + 1, i, j, start, end));
+                }
+                _ => (),
+            }
+        }
+    }
+    tunnels.sort_by_key(|t| t.0);
+
+    // Kruskal's algorithm with union-find
+    let mut parent: Vec<usize> = (0..regions.len()).collect();
+    for (_, i, j, start, end) in tunnels.into_iter() {
+        let root_i = find_root(&mut parent, i);
+        let root_j = find_root(&mut parent, j);
+        if root_i == root_j { continue; }
+        parent[root_i] = root_j;
+        dig_tunnel(gm, start, end);
+    }
+}
+
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Returns separated passable regions.
+/// Tiles are connected only in four directions.
+pub fn split_regions(gm: &GeneratedMap) -> Vec<Vec<Vec2d>> {
+    let mut checked = Array2d::new(gm.size.0 as u32, gm.size.1 as u32, false);
+    let mut regions = Vec::new();
+
+    for p in gm.tile.iter_idx() {
+        if checked[p] || !gm.tile[p].is_passable() { continue; }
+
+        let mut region = Vec::new();
+        let mut queue = VecDeque::new();
+        checked[p] = true;
+        queue.push_back(p);
+
+        while let Some(q) = queue.pop_front() {
+            region.push(q);
+            for d in &FOUR_DIRS {
+                let next = q + *d;
+                if gm.tile.in_range(next) && !checked[next] && gm.tile[next].is_passable() {
+                    checked[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// Dig a tunnel which is passable in four directions
+fn dig_tunnel(gm: &mut GeneratedMap, start: Vec2d, end: Vec2d) {
+    let mut p = start;
+
+    while p != end {
+        let dx = end.0 - p.0;
+        let dy = end.1 - p.1;
+        // Choose horizontal or vertical step randomly to avoid straight tunnels
+        if dy == 0 || (dx != 0 && gen_range(0, dx.abs() + dy.abs()) < dx.abs()) {
+            p.0 += dx.signum();
+        } else {
+            p.1 += dy.signum();
+        }
+        if gm.tile[p] == TileKind::Wall {
+            gm.tile[p] = TileKind::Floor;
+        }
+    }
+}
@@ -55,6 +55,18 @@ pub fn reseed() {
     })
 }
 
+/// Reseed by the given value to reproduce the same random sequence
+pub fn reseed_with(seed: u64) {
+    let mut s = [0u8; 16];
+    for i in 0..8 {
+        s[i] = (seed >> (i * 8)) as u8;
+        s[i + 8] = (!seed >> (i * 8)) as u8;
+    }
+    XORSHIFT_RNG.with(|xorshift_rng| {
+        xorshift_rng.replace(XorShiftRng::from_seed(s));
+    })
+}
+
 pub fn next_u32() -> u32 {
     let mut rng = GameRng;
     rng.next_u32()
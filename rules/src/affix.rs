@@ -0,0 +1,56 @@
+
+use common::gamedata::*;
+
+/// Rules for randomized item affixes
+#[derive(Serialize, Deserialize)]
+pub struct Affix {
+    /// The probability that a prefix is added to a generated item
+    pub prefix_probability: f64,
+    /// The probability that a suffix is added to a generated item
+    pub suffix_probability: f64,
+    pub prefixes: Vec<AffixParams>,
+    pub suffixes: Vec<AffixParams>,
+}
+
+impl Affix {
+    /// Find prefix or suffix by id
+    pub fn get(&self, id: &str) -> Option<&AffixParams> {
+        self.prefixes.iter().chain(self.suffixes.iter()).find(|affix| affix.id == id)
+    }
+
+    pub fn is_prefix(&self, id: &str) -> bool {
+        self.prefixes.iter().any(|affix| affix.id == id)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AffixParams {
+    pub id: String,
+    /// Minimum floor level to generate
+    pub level: u32,
+    pub weight: f64,
+    /// Item kinds which can have this affix
+    pub targets: Vec<ItemKindRough>,
+    pub effects: Vec<AffixEffect>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum AffixEffect {
+    /// Increase character attributes
+    Attribute { attr: AttrKind, value: u16 },
+    /// Additional damage of the element on attack
+    ElementalDamage { element: Element, value: u16 },
+    /// Reduce damage of the element (%)
+    Resistance { element: Element, value: u16 },
+    /// Increase skill level
+    Skill { skill: SkillKind, value: u16 },
+    /// Recover hp by the given percentage of inflicted damage
+    Lifesteal { percent: u16 },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum AttrKind {
+    Str, Vit, Dex, Int, Wil, Cha, Spd,
+}
@@ -15,5 +15,39 @@ pub struct Chara {
     pub sp_weak: i32,
     /// sp border of starving
     pub sp_starving: i32,
+    /// Max mp is calculated as (int + wil) * max_mp_factor
+    pub max_mp_factor: i32,
+    /// Character's mp is increased by this value per turn.
+    pub mp_regeneration: i32,
+    /// Status turns are multiplied by factor / (factor + resistance param)
+    pub status_resistance_factor: u16,
+    /// Damage per turn by poison
+    pub poison_strength: u16,
+    /// The percentage of confused characters' random movement
+    pub confuse_strength: u16,
+    /// View range decrease by blindness
+    pub blind_strength: u16,
+    /// Damage per turn by bleeding
+    pub bleeding_strength: u16,
+    /// Damage per turn by burning
+    pub burning_strength: u16,
+    /// Speed increase by haste is spd * this rate
+    pub haste_spd_rate: f32,
+    /// Carrying capacity (gram) is calculated as (str + vit) * carry_capacity_factor
+    pub carry_capacity_factor: u32,
+    /// Weight / capacity border of burdened
+    pub burdened_ratio: f32,
+    /// Weight / capacity border of stressed
+    pub stressed_ratio: f32,
+    /// Weight / capacity border of overloaded
+    pub overloaded_ratio: f32,
+    /// Characters cannot pick up items over this weight / capacity
+    pub max_carry_ratio: f32,
+    /// Speed is multiplied by this value when burdened
+    pub burdened_spd_rate: f32,
+    /// Speed is multiplied by this value when stressed
+    pub stressed_spd_rate: f32,
+    /// Speed is multiplied by this value when overloaded
+    pub overloaded_spd_rate: f32,
 }
 
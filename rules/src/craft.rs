@@ -0,0 +1,17 @@
+
+/// Rules for crafting and cooking
+#[derive(Serialize, Deserialize)]
+pub struct Craft {
+    /// Success chance when the skill level equals to the recipe difficulty
+    pub base_success_chance: f32,
+    /// Success chance changes by this value per skill level over the difficulty
+    pub success_chance_per_level: f32,
+    /// The minimum success chance
+    pub min_success_chance: f32,
+    /// The base rank of crafted items increases by this value per skill level over the difficulty
+    pub quality_per_level: f32,
+    /// The absolute value of crafted items' base rank is limited by this value
+    pub max_quality: i8,
+    /// Nutrition of food increases by this rate per base rank
+    pub nutrition_per_rank: f32,
+}
@@ -10,13 +10,191 @@ pub type DungeonGen = HashMap<DungeonKind, DungeonGenParams>;
 pub struct DungeonGenParams {
     /// Default map size
     pub map_size: Vec2d,
+    /// Map generation algorithm
+    pub map_gen: MapGenKind,
     /// The probability of npc generation for each race
     pub npc_race_probability: HashMap<Race, f32>,
-    /// Tile and wall ids
-    pub terrain: Vec<[String; 2]>,
+    /// Terrain sets. One of them is chosen for each floor.
+    pub terrain: Vec<TerrainSet>,
     /// Items generatation probability on each tile
     pub item_gen_probability: f64,
     /// The range of number of floor of auto generated dungeons
     pub floor_range: [u32; 2],
+    /// Map templates stitched into generated floors
+    #[serde(default)]
+    pub prefabs: Vec<PrefabParams>,
+    /// Room feature generation parameters
+    pub room_feature: RoomFeatureParams,
+    /// Trap generation parameters
+    pub trap: TrapGenParams,
+    /// The range of number of containers on each floor
+    pub n_container: [u32; 2],
+    /// The probability that a door or a container is locked
+    pub lock_probability: f64,
+    /// Terrain condition for the site position on region maps
+    #[serde(default)]
+    pub site_terrain: SiteTerrain,
+    /// Cleared dungeons disappear after these days
+    pub expire_days: u32,
+    /// Monsters respawn on floors which the player left before these days
+    pub respawn_days: u32,
+    /// The range of the number of respawned monsters
+    pub n_respawn: [u32; 2],
+    /// Themes. One of them is chosen for each dungeon.
+    #[serde(default)]
+    pub themes: Vec<DungeonTheme>,
+}
+
+impl DungeonGenParams {
+    pub fn theme(&self, id: &str) -> Option<&DungeonTheme> {
+        self.themes.iter().find(|theme| theme.id == id)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DungeonTheme {
+    /// Theme id. The dungeon name is given by text id "!dungeon_theme.<id>".
+    pub id: String,
+    /// Weight to be chosen
+    pub weight: f32,
+    /// Chara template id of the boss on the deepest floor
+    #[serde(default)]
+    pub boss: Option<String>,
+    /// Item ids generated in this dungeon. Items are chosen from all items if empty.
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// Music played in this dungeon
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Map generation algorithm used instead of the default
+    #[serde(default)]
+    pub map_gen: Option<MapGenKind>,
+}
+
+/// Terrain condition for dungeon sites on region maps
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum SiteTerrain {
+    Any,
+    /// Next to mountains or other walls
+    NearMountain,
+    /// Open land without walls and water around
+    Plain,
+    /// Next to water
+    Coast,
+}
+
+impl Default for SiteTerrain {
+    fn default() -> SiteTerrain {
+        SiteTerrain::Any
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TerrainSet {
+    /// Base tile id
+    pub tile: String,
+    /// Wall id
+    pub wall: String,
+    /// Weight to be chosen
+    pub weight: f32,
+    /// The range of floor levels this set is used on. Used on any floor if omitted.
+    #[serde(default)]
+    pub floor_range: Option<[u32; 2]>,
+    /// Tiles blended onto the base tile by noise. Each one uses the next tile layer.
+    #[serde(default)]
+    pub layers: Vec<TerrainLayer>,
+    /// Decoration ids sprinkled on floor tiles
+    #[serde(default)]
+    pub deco: Vec<String>,
+    /// The probability of decoration on each floor tile
+    #[serde(default)]
+    pub deco_density: f64,
+}
+
+impl TerrainSet {
+    pub fn is_available(&self, floor: u32) -> bool {
+        if let Some(floor_range) = self.floor_range {
+            floor_range[0] <= floor && floor <= floor_range[1]
+        } else {
+            true
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PrefabParams {
+    /// Map template id
+    pub id: String,
+    /// The probability to be located on each floor
+    pub probability: f64,
+    /// The range of floor levels this prefab appears. Appears on any floor if omitted.
+    #[serde(default)]
+    pub floor_range: Option<[u32; 2]>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TerrainLayer {
+    pub tile: String,
+    /// The ratio of tiles covered by this layer
+    pub ratio: f32,
+}
+
+/// Map generation algorithm and its parameters
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum MapGenKind {
+    Fractal,
+    /// Rooms and corridors by binary space partition
+    Bsp {
+        /// Minimum and maximum length of room sides
+        room_size: [u32; 2],
+        corridor_width: u32,
+    },
+    /// Cave by cellular automata
+    Cave {
+        /// Initial ratio of walls
+        fill_ratio: f64,
+        n_smoothing: u32,
+    },
+}
+
+impl Default for MapGenKind {
+    fn default() -> MapGenKind {
+        MapGenKind::Fractal
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TrapGenParams {
+    /// The number of traps on a floor is n_base + n_per_floor * floor
+    pub n_base: f32,
+    pub n_per_floor: f32,
+    /// Trap kinds and the minimum floor they appear
+    pub kinds: Vec<(TrapKind, u32)>,
+    /// The probability that a trap is located at chokepoints or corridors
+    pub chokepoint_ratio: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RoomFeatureParams {
+    /// The probability that a room becomes a vault closed by locked doors
+    pub vault: f64,
+    /// Rooms which have more exit tiles than this cannot be vaults
+    pub vault_max_exits: u32,
+    /// Vault doors' lock level is floor level + this
+    pub vault_lock_level: u16,
+    /// The probability that a lever opening a vault door is placed outside the vault
+    pub vault_lever: f64,
+    /// The probability that a room becomes a treasure room
+    pub treasure_room: f64,
+    /// The range of number of items in vaults and treasure rooms
+    pub n_treasure: [u32; 2],
+    /// The probability that a room becomes a monster nest
+    pub monster_nest: f64,
+    /// The range of number of monsters in a nest
+    pub n_nest_monster: [u32; 2],
+    /// The probability of item generation on each dead end
+    pub dead_end_item: f64,
 }
 
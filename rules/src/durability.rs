@@ -0,0 +1,15 @@
+
+/// Rules for equipment damage and repair
+#[derive(Serialize, Deserialize)]
+pub struct Durability {
+    /// The probability that the weapon is damaged when its wielder hits
+    pub weapon_damage_probability: f64,
+    /// The probability that one of armors is damaged when its wearer is hit
+    pub armor_damage_probability: f64,
+    /// Equipments are not damaged more than this value
+    pub max_damage: i8,
+    /// Dice and defence of equipments decrease by this rate per damage
+    pub penalty_per_damage: f32,
+    /// Repair fee at shops per damage is this rate of the item price
+    pub repair_fee_rate: f32,
+}
@@ -8,5 +8,7 @@ pub struct Exp {
     pub adjust_coeff: Vec<f32>,
     /// Base exp after attacking
     pub attack: u32,
+    /// Base exp after casting a spell
+    pub cast: u32,
 }
 
@@ -0,0 +1,29 @@
+
+use std::collections::HashMap;
+use common::gamedata::*;
+
+/// Rules for character level and attribute growth
+#[derive(Serialize, Deserialize)]
+pub struct Growth {
+    /// Base exp gained by killing a character of the same level
+    pub kill_exp: u32,
+    /// Kill exp is multiplied by (target level / killer level) ^ kill_exp_level_exponent
+    pub kill_exp_level_exponent: f32,
+    /// Base level exp gained by skill use is skill exp multiplied by this value
+    pub skill_exp_ratio: f32,
+    /// Parameter increase per level for each class
+    pub class_growth: HashMap<CharaClass, ClassGrowth>,
+}
+
+/// Parameter increase per level.
+/// The fractional part of attributes is used as the probability of additional increase.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ClassGrowth {
+    pub max_hp: i32,
+    pub str: f32,
+    pub vit: f32,
+    pub dex: f32,
+    pub int: f32,
+    pub wil: f32,
+    pub cha: f32,
+}
@@ -0,0 +1,11 @@
+
+/// Rules for item identification
+#[derive(Serialize, Deserialize)]
+pub struct Identify {
+    /// Appearances given to unknown potions. They are shuffled for each game.
+    pub potion_appearances: Vec<String>,
+    /// Selling price of unidentified items is multiplied by this rate
+    pub unidentified_price_rate: f32,
+    /// Identification fee at shops is this rate of the item price
+    pub shop_fee_rate: f32,
+}
@@ -10,12 +10,19 @@ extern crate serde_json;
 extern crate rusted_ruins_array2d as array2d;
 extern crate rusted_ruins_common as common;
 
+pub mod affix;
 pub mod chara;
 pub mod charagen;
+pub mod craft;
 pub mod dungeon_gen;
+pub mod durability;
 pub mod exp;
+pub mod growth;
+pub mod identify;
 pub mod newgame;
 pub mod params;
+pub mod region_gen;
+pub mod skill;
 pub mod town;
 
 use std::path::{Path, PathBuf};
@@ -25,24 +32,38 @@ use serde::de::Deserialize;
 
 /// Contain game rules
 pub struct Rules {
+    pub affix: affix::Affix,
     pub chara: chara::Chara,
     pub chara_gen: charagen::CharaGen,
+    pub craft: craft::Craft,
     pub dungeon_gen: dungeon_gen::DungeonGen,
+    pub durability: durability::Durability,
     pub exp: exp::Exp,
+    pub growth: growth::Growth,
+    pub identify: identify::Identify,
     pub newgame: newgame::NewGame,
     pub params: params::Params,
+    pub region_gen: region_gen::RegionGen,
+    pub skill: skill::Skill,
     pub town: town::Town,
 }
 
 impl Rules {
     fn load_from_dir(rules_dir: &Path) -> Rules {
         Rules {
+            affix:       read_from_json(&rules_dir.join("affix.json")),
             chara:       read_from_json(&rules_dir.join("chara.json")),
             chara_gen:   read_from_json(&rules_dir.join("charagen.json")),
+            craft:       read_from_json(&rules_dir.join("craft.json")),
             dungeon_gen: read_from_json(&rules_dir.join("dungeon_gen.json")),
+            durability:  read_from_json(&rules_dir.join("durability.json")),
             exp:         read_from_json(&rules_dir.join("exp.json")),
+            growth:      read_from_json(&rules_dir.join("growth.json")),
+            identify:    read_from_json(&rules_dir.join("identify.json")),
             newgame:     read_from_json(&rules_dir.join("newgame.json")),
             params:      read_from_json(&rules_dir.join("params.json")),
+            region_gen:  read_from_json(&rules_dir.join("region_gen.json")),
+            skill:       read_from_json(&rules_dir.join("skill.json")),
             town:        read_from_json(&rules_dir.join("town.json")),
         }
     }
@@ -13,5 +13,9 @@ pub struct NewGame {
     pub start_money: u32,
     pub chara_template_table: HashMap<CharaClass, String>,
     pub common_initial_skills: Vec<SkillKind>,
+    /// Additional skills for each class
+    pub class_initial_skills: HashMap<CharaClass, Vec<SkillKind>>,
+    /// Spell ids which each class has learned at the beginning
+    pub class_initial_spells: HashMap<CharaClass, Vec<String>>,
 }
 
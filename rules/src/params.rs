@@ -14,5 +14,9 @@ pub struct Params {
     pub initial_date_day: u32,
     /// Initial game date (hour)
     pub initial_date_hour: u32,
+    /// The probability that a new dungeon appears in the current region each day
+    pub dungeon_spawn_probability: f64,
+    /// Music played on region maps
+    pub region_music: String,
 }
 
@@ -0,0 +1,29 @@
+
+use std::collections::HashMap;
+use array2d::Vec2d;
+
+/// Rules for procedural region map generation by region id.
+/// Regions not listed here are created from their map templates.
+pub type RegionGen = HashMap<String, RegionGenParams>;
+
+#[derive(Serialize, Deserialize)]
+pub struct RegionGenParams {
+    /// Region map size
+    pub map_size: Vec2d,
+    /// Tile id for land
+    pub ground: String,
+    /// Tile id for sea and lakes
+    pub water: String,
+    /// Tile id for roads between towns
+    pub road: String,
+    /// Wall id for mountains
+    pub mountain: String,
+    /// Decoration ids for forests
+    pub forest: Vec<String>,
+    /// The ratio of water tiles. The lowest parts of the heightmap become water.
+    pub water_ratio: f32,
+    /// The ratio of mountain tiles. The highest parts of the heightmap become mountains.
+    pub mountain_ratio: f32,
+    /// The ratio of forest tiles
+    pub forest_ratio: f32,
+}
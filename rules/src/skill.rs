@@ -0,0 +1,15 @@
+
+use std::collections::HashMap;
+
+/// Definitions of non-combat skills keyed by skill id
+pub type Skill = HashMap<String, SkillParams>;
+
+#[derive(Serialize, Deserialize)]
+pub struct SkillParams {
+    /// Base exp when this skill is used
+    pub exp: u32,
+    /// Effect of this skill increases by this value per skill level
+    pub effect_per_level: f32,
+    /// The maximum effect of this skill
+    pub max_effect: f32,
+}
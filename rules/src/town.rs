@@ -6,4 +6,32 @@ pub struct Town {
     pub min_shop_items: u32,
     /// The maximum number of shop items
     pub max_shop_items: u32,
+    /// Shops restock items at this interval of days
+    pub restock_days: u32,
+    /// The probability that each item stack is kept at restocking
+    pub stock_keep_probability: f64,
+    /// Shops have this amount of money per shop level after restocking
+    pub shop_money_per_level: i64,
+    /// Prices decrease by this rate per supply of the item kind
+    pub supply_price_rate: f32,
+    /// Prices are not decreased under this rate by supply
+    pub min_supply_price_rate: f32,
+    /// Items sold by the player are sold back at their selling price multiplied by this rate
+    pub buyback_price_rate: f32,
+    /// The numbers of sold items are multiplied by this rate at restocking
+    pub sold_decay_rate: f32,
+    /// Prosperity of new towns
+    pub initial_prosperity: u32,
+    /// Town prosperity increases by 1 per this amount of trading money
+    pub money_per_prosperity: i64,
+    /// Shop level increases by 1 per this prosperity
+    pub prosperity_per_shop_level: u32,
+    /// Healing fee per the player's level
+    pub heal_fee_per_level: i64,
+    /// Fee to stay at inns
+    pub inn_fee: i64,
+    /// Hours passed by staying at inns
+    pub inn_hours: u32,
+    /// Music played in towns
+    pub music: String,
 }
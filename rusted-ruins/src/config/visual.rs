@@ -39,14 +39,18 @@ pub struct UIConfig {
     pub start_dialog: StartDialogConfig,
     pub msg_dialog: MsgDialogConfig,
     pub text_input_dialog: TextInputDialogConfig,
+    pub quantity_dialog: QuantityDialogConfig,
     pub newgame_dialog: NewGameDialogConfig,
     pub choose_class_dialog: ChooseClassDialogConfig,
     pub item_window: ItemWindowConfig,
+    pub item_info_window: ItemInfoWindowConfig,
     pub equip_window: EquipWindowConfig,
     pub scrolling_text_window: ScrollingTextWindowConfig,
     pub status_window: StatusWindowConfig,
     pub game_info_window: GameInfoWindowConfig,
     pub skill_window: SkillWindowConfig,
+    pub spell_window: SpellWindowConfig,
+    pub craft_window: CraftWindowConfig,
     pub page_window: PageWindowConfig,
     pub label_widget: LabelWidgetConfig,
     pub list_widget: ListWidgetConfig,
@@ -106,6 +110,12 @@ pub struct TalkWindowConfig {
     pub image_window_pos_y: i32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ItemInfoWindowConfig {
+    pub rect: CfgRect,
+    pub n_max_line: usize,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct MsgDialogConfig {
     pub rect: CfgRect,
@@ -127,6 +137,11 @@ pub struct TextInputDialogConfig {
     pub rect: CfgRect,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct QuantityDialogConfig {
+    pub rect: CfgRect,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct NewGameDialogConfig {
     pub explanation_text_rect: CfgRect,
@@ -142,6 +157,8 @@ pub struct ItemWindowConfig {
     pub rect: CfgRect,
     pub n_row: u32,
     pub column_pos: Vec<i32>,
+    pub weight_label_rect: CfgRect,
+    pub tab_label_rect: CfgRect,
 }
 
 #[derive(Debug, Deserialize)]
@@ -157,12 +174,16 @@ pub struct StatusWindowConfig {
     pub image_rect: CfgRect,
     pub name_label_rect: CfgRect,
     pub hp_label_rect: CfgRect,
+    pub mp_label_rect: CfgRect,
     pub str_label_rect: CfgRect,
     pub vit_label_rect: CfgRect,
     pub dex_label_rect: CfgRect,
     pub int_label_rect: CfgRect,
     pub wil_label_rect: CfgRect,
     pub cha_label_rect: CfgRect,
+    pub weight_label_rect: CfgRect,
+    pub status_label_rect: CfgRect,
+    pub status_label_w: u32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -177,6 +198,18 @@ pub struct SkillWindowConfig {
     pub n_row: u32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SpellWindowConfig {
+    pub rect: CfgRect,
+    pub n_row: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CraftWindowConfig {
+    pub rect: CfgRect,
+    pub n_row: u32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PageWindowConfig {
     pub rect: CfgRect,
@@ -36,10 +36,12 @@ impl BackgroundDrawInfo {
         di.tile = tile;
 
         if map.is_inside(pos) {
-            if let Some(special_tile_id) = map.observed_tile[pos].special.obj_id() {
-                let special_tile_obj: &'static SpecialTileObject = gobj::get_by_id(special_tile_id);
+            // Special tiles whose objects are not loaded are not drawn
+            let special_tile_idx: Option<SpecialTileIdx> = map.observed_tile[pos].special.obj_id()
+                .and_then(|special_tile_id| gobj::id_to_idx_checked(special_tile_id));
+            if let Some(special_tile_idx) = special_tile_idx {
+                let special_tile_obj: &'static SpecialTileObject = gobj::get_obj(special_tile_idx);
                 if special_tile_obj.always_background {
-                    let special_tile_idx: SpecialTileIdx = gobj::id_to_idx(special_tile_id);
                     di.special = Some(special_tile_idx);
                 }
             }
@@ -67,10 +69,12 @@ impl ForegroundDrawInfo {
         let mut di = ForegroundDrawInfo::default();
 
         if map.is_inside(pos) {
-            if let Some(special_tile_id) = map.observed_tile[pos].special.obj_id() {
-                let special_tile_obj: &'static SpecialTileObject = gobj::get_by_id(special_tile_id);
+            // Special tiles whose objects are not loaded are not drawn
+            let special_tile_idx: Option<SpecialTileIdx> = map.observed_tile[pos].special.obj_id()
+                .and_then(|special_tile_id| gobj::id_to_idx_checked(special_tile_id));
+            if let Some(special_tile_idx) = special_tile_idx {
+                let special_tile_obj: &'static SpecialTileObject = gobj::get_obj(special_tile_idx);
                 if !special_tile_obj.always_background {
-                    let special_tile_idx: SpecialTileIdx = gobj::id_to_idx(special_tile_id);
                     di.special = Some(special_tile_idx);
                 }
             }
@@ -3,15 +3,25 @@
 
 use super::Game;
 use super::combat;
+use super::special_tile;
 use super::extrait::*;
 use array2d::*;
 use common::gamedata::*;
 use common::gobj;
+use rng::{self, Rng};
+use rules::RULES;
 
 pub fn try_move(game: &mut Game, chara_id: CharaId, dir: Direction) -> bool {
     if dir.as_vec() == (0, 0) { return true; } // Move to current tile always success
+    let dir = confuse_dir(game.gd.chara.get(chara_id), dir);
+    if dir.as_vec() == (0, 0) { return true; }
     let dest_tile = game.gd.get_current_map().chara_pos(chara_id).unwrap() + dir.as_vec();
 
+    // Open a closed door or a locked container
+    if special_tile::bump_special_tile(game, chara_id, dest_tile) {
+        return true;
+    }
+
     if !game.gd.get_current_map().is_passable(game.gd.chara.get(chara_id), dest_tile) {
         return false;
     }
@@ -22,6 +32,7 @@ pub fn try_move(game: &mut Game, chara_id: CharaId, dir: Direction) -> bool {
         if chara_id == CharaId::Player {
             game.anim_queue.push_player_move(dir);
         }
+        special_tile::trigger_trap(game, chara_id, dest_tile);
     }else{
         let rel = {
             let chara = game.gd.chara.get(chara_id);
@@ -37,6 +48,7 @@ pub fn try_move(game: &mut Game, chara_id: CharaId, dir: Direction) -> bool {
                 if chara_id == CharaId::Player {
                     game.anim_queue.push_player_move(dir);
                 }
+                special_tile::trigger_trap(game, chara_id, dest_tile);
             },
             Relationship::HOSTILE => {
                 combat::attack_neighbor(game, chara_id, other_chara.unwrap());
@@ -46,6 +58,23 @@ pub fn try_move(game: &mut Game, chara_id: CharaId, dir: Direction) -> bool {
     true
 }
 
+/// Confused characters move to random direction sometimes
+fn confuse_dir(chara: &Chara, dir: Direction) -> Direction {
+    for s in chara.status.iter() {
+        match *s {
+            CharaStatus::Confused { strength, .. } => {
+                if rng::gen_range(0, 100) < strength {
+                    return Direction::new(
+                        *rng::get_rng().choose(&[HDirection::Left, HDirection::None, HDirection::Right]).unwrap(),
+                        *rng::get_rng().choose(&[VDirection::Up, VDirection::None, VDirection::Down]).unwrap());
+                }
+            }
+            _ => (),
+        }
+    }
+    dir
+}
+
 /// Shot target
 pub fn shot_target(game: &mut Game, cid: CharaId, target: CharaId) -> bool {
     combat::shot_target(game, cid, target)
@@ -70,28 +99,54 @@ pub fn eat_item(gd: &mut GameData, il: ItemLocation, cid: CharaId) {
     
     let chara = gd.chara.get_mut(cid);
     game_log!("eat-item"; chara=chara, item=item);
-    chara.add_sp(item_obj.nutrition.into(), cid);
+    let nutrition = item_obj.nutrition as f32 * (1.0 + item.rank.base as f32 * RULES.craft.nutrition_per_rank);
+    chara.add_sp(nutrition as i32, cid);
 
     let eff: i32 = item_obj.eff.into();
     apply_medical_effect(chara, item_obj.medical_effect, eff);
 }
 
 fn apply_medical_effect(chara: &mut Chara, me: MedicalEffect, eff: i32) {
+    let turn = eff as u16;
     match me {
         MedicalEffect::None => (),
         MedicalEffect::Heal => {
             use std::cmp::min;
+            let eff = (eff as f32 * (1.0 + chara.skill_effect(skill_id::HEALING))) as i32;
             chara.hp = min(chara.params.max_hp, chara.hp + eff);
             game_log!("heal-hp"; chara=chara, value=eff);
+            chara.add_skill_use_exp(skill_id::HEALING);
         }
         MedicalEffect::Sleep => {
-            chara.add_status(CharaStatus::Asleep { turn_left: eff as u16 });
-            game_log!("fall-asleep"; chara=chara);
+            if chara.apply_status(CharaStatus::Asleep { turn_left: turn }) {
+                game_log!("fall-asleep"; chara=chara);
+            }
         }
         MedicalEffect::Poison => {
-            chara.add_status(CharaStatus::Poisoned);
-            game_log!("poisoned"; chara=chara);
+            if chara.apply_status(CharaStatus::Poisoned { turn_left: turn, strength: RULES.chara.poison_strength }) {
+                game_log!("poisoned"; chara=chara);
+            }
+        }
+        MedicalEffect::Confuse => {
+            if chara.apply_status(CharaStatus::Confused { turn_left: turn, strength: RULES.chara.confuse_strength }) {
+                game_log!("confused"; chara=chara);
+            }
+        }
+        MedicalEffect::Paralyze => {
+            if chara.apply_status(CharaStatus::Paralyzed { turn_left: turn }) {
+                game_log!("paralyzed"; chara=chara);
+            }
+        }
+        MedicalEffect::Blind => {
+            if chara.apply_status(CharaStatus::Blind { turn_left: turn, strength: RULES.chara.blind_strength }) {
+                game_log!("blinded"; chara=chara);
+            }
+        }
+        MedicalEffect::Haste => {
+            let strength = (chara.base_params.spd as f32 * RULES.chara.haste_spd_rate) as u16;
+            chara.apply_status(CharaStatus::Hasted { turn_left: turn, strength });
+            game_log!("hasted"; chara=chara);
         }
     }
+    super::chara::update_params(chara);
 }
-
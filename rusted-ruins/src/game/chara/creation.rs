@@ -13,7 +13,7 @@ pub fn create_chara(chara_template_idx: CharaTemplateIdx) -> Chara {
     let max_hp = ct.max_hp;
 
     let base_params = CharaBaseParams {
-        level: 1,
+        level: BaseLevel::new(::std::cmp::max(ct.gen_level as u16, 1)),
         max_hp: max_hp,
         str: ct.str,
         vit: ct.vit,
@@ -37,18 +37,26 @@ pub fn create_chara(chara_template_idx: CharaTemplateIdx) -> Chara {
         hp: max_hp,
         status: Vec::new(),
         sp: RULES.chara.default_sp,
+        mp: 0,
         skills: SkillList::default(),
+        spells: SpellList::new(),
         rel: Relationship::NEUTRAL,
         trigger_talk: None,
     };
     super::update_params(&mut chara);
     chara.hp = chara.base_params.max_hp;
+    chara.mp = chara.params.max_mp;
     chara
 }
 
 /// Create npc character from the race
 pub fn create_npc_chara(dungeon: DungeonKind, floor_level: u32) -> Chara {    
-    let mut chara = create_chara(choose_npc_chara_template(dungeon, floor_level));
+    create_hostile_npc_chara(choose_npc_chara_template(dungeon, floor_level))
+}
+
+/// Create hostile npc character from chara_template
+pub fn create_hostile_npc_chara(chara_template_idx: CharaTemplateIdx) -> Chara {
+    let mut chara = create_chara(chara_template_idx);
     set_skill(&mut chara);
     chara.rel = Relationship::HOSTILE;
     return chara;
@@ -93,6 +101,32 @@ fn choose_npc_chara_template(dungeon: DungeonKind, floor_level: u32) -> CharaTem
     CharaTemplateIdx(first_available_ct_idx.unwrap() as u32)
 }
 
+/// The expected level of npcs generated on the floor of the dungeon.
+/// Uses the same weights as choosing chara_template.
+pub fn expected_npc_level(dungeon: DungeonKind, floor_level: u32) -> f64 {
+    let dungeon_gen_params = RULES.dungeon_gen.get(&dungeon).expect("No rule for npc generation");
+    let nrp = &dungeon_gen_params.npc_race_probability;
+    let chara_templates = &gobj::get_objholder().chara_template;
+
+    let weight_dist = CalcLevelWeightDist::new(floor_level);
+    let mut sum = 0.0;
+    let mut level_sum = 0.0;
+
+    for ct in chara_templates.iter() {
+        if let Some(da) = nrp.get(&ct.race) {
+            let w = weight_dist.calc(ct.gen_level) * ct.gen_weight as f64 * *da as f64;
+            sum += w;
+            level_sum += w * ::std::cmp::max(ct.gen_level, 1) as f64;
+        }
+    }
+
+    if sum > 0.0 && level_sum.is_finite() {
+        level_sum / sum
+    } else {
+        ::std::cmp::max(floor_level, 1) as f64
+    }
+}
+
 struct CalcLevelWeightDist {
     floor_level: f64,
     upper_margin: f64,
@@ -0,0 +1,47 @@
+//! Functions for character level and attribute growth
+
+use common::gamedata::*;
+use rules::RULES;
+use rng;
+
+/// Increase character parameters by class growth rule
+pub fn level_up(chara: &mut Chara) {
+    let growth = if let Some(growth) = RULES.growth.class_growth.get(&chara.class) {
+        *growth
+    } else {
+        warn!("No growth rule for {:?}", chara.class);
+        return;
+    };
+
+    let bp = &mut chara.base_params;
+    bp.max_hp += growth.max_hp;
+    bp.str = bp.str.saturating_add(roll_growth(growth.str));
+    bp.vit = bp.vit.saturating_add(roll_growth(growth.vit));
+    bp.dex = bp.dex.saturating_add(roll_growth(growth.dex));
+    bp.int = bp.int.saturating_add(roll_growth(growth.int));
+    bp.wil = bp.wil.saturating_add(roll_growth(growth.wil));
+    bp.cha = bp.cha.saturating_add(roll_growth(growth.cha));
+    chara.hp += growth.max_hp;
+}
+
+/// The fractional part is used as the probability of additional increase
+fn roll_growth(v: f32) -> u16 {
+    let base = v.floor();
+    let frac = v - base;
+    let add = if rng::gen_range(0.0, 1.0) < frac { 1 } else { 0 };
+    base as u16 + add
+}
+
+/// Calculate exp gained by killing a character
+pub fn calc_kill_exp(killer_level: u16, target_level: u16) -> u32 {
+    use common::basic::BASE_LEVEL_EXP_LVUP;
+    let killer_level = ::std::cmp::max(killer_level, 1) as f32;
+    let target_level = target_level as f32;
+    let r = &RULES.growth;
+    let exp = r.kill_exp as f32 * (target_level / killer_level).powf(r.kill_exp_level_exponent);
+    if exp > BASE_LEVEL_EXP_LVUP as f32 {
+        BASE_LEVEL_EXP_LVUP as u32
+    } else {
+        exp as u32
+    }
+}
@@ -1,10 +1,12 @@
 
 pub mod creation;
+pub mod growth;
 pub mod preturn;
 pub mod status;
 
 use common::gamedata::*;
 use rules::RULES;
+use rules::affix::{AffixEffect, AttrKind};
 use text::ToText;
 use super::Game;
 use super::extrait::*;
@@ -13,18 +15,65 @@ use super::combat::DamageKind;
 /// Additional Chara method
 pub trait CharaEx {
     fn add_skill_exp(&mut self, kind: SkillKind, add_exp: u32, base_level: u16);
+    /// Add exp to character's base level. Parameters grow when level up.
+    fn add_base_exp(&mut self, add_exp: u32);
+    /// Add exp by using a non-combat skill
+    fn add_skill_use_exp(&mut self, id: &str);
+    /// Effect of a non-combat skill calculated from the skill level
+    fn skill_effect(&self, id: &str) -> f32;
     /// sp increase/decrease.
     fn add_sp(&mut self, v: i32, cid: CharaId);
+    /// mp increase/decrease. mp is limited by max_mp.
+    fn add_mp(&mut self, v: i32);
+    /// Total weight of items and equipments (gram)
+    fn carrying_w(&self) -> u32;
+    /// Carrying capacity (gram) calculated from str and vit
+    fn carry_capacity(&self) -> u32;
+    /// Update burden status by carrying weight
+    fn update_burden(&mut self);
 }
 
 impl CharaEx for Chara {
     fn add_skill_exp(&mut self, kind: SkillKind, add_exp: u32, base_level: u16) {
-        let result = self.skills.add_exp(kind, add_exp, base_level);
+        let result = self.skills.add_exp(kind.clone(), add_exp, base_level);
         trace!("{} gains {} exp for {:?}", self.to_text(), result.1, kind);
         if result.0 { // If level up
             trace!("{} level up ({:?})", self.to_text(), kind);
             game_log!("skill-level-up"; chara=self, skill=kind);
         }
+        let base_exp = (result.1 as f32 * RULES.growth.skill_exp_ratio) as u32;
+        if base_exp > 0 {
+            self.add_base_exp(base_exp);
+        }
+    }
+
+    fn add_skill_use_exp(&mut self, id: &str) {
+        if let Some(params) = RULES.skill.get(id) {
+            let base_level = self.base_params.level.level();
+            self.add_skill_exp(SkillKind::non_combat(id), params.exp, base_level);
+        }
+    }
+
+    fn skill_effect(&self, id: &str) -> f32 {
+        if let Some(params) = RULES.skill.get(id) {
+            let effect = self.skills.get(&SkillKind::non_combat(id)) as f32 * params.effect_per_level;
+            if effect > params.max_effect { params.max_effect } else { effect }
+        } else {
+            0.0
+        }
+    }
+
+    fn add_base_exp(&mut self, add_exp: u32) {
+        let gained_levels = self.base_params.level.add_exp(add_exp);
+        if gained_levels > 0 {
+            for _ in 0..gained_levels {
+                growth::level_up(self);
+            }
+            update_params(self);
+            trace!("{} base level up", self.to_text());
+            let level = self.base_params.level.level();
+            game_log!("base-level-up"; chara=self, level=level);
+        }
     }
 
     fn add_sp(&mut self, v: i32, cid: CharaId) {
@@ -62,6 +111,46 @@ impl CharaEx for Chara {
             }
         }
     }
+
+    fn add_mp(&mut self, v: i32) {
+        use std::cmp::{min, max};
+        self.mp = max(0, min(self.params.max_mp, self.mp + v));
+    }
+
+    fn carrying_w(&self) -> u32 {
+        let items_w: u32 = self.item_list.iter().map(|&(ref item, n)| item.w() * n).sum();
+        let equip_w: u32 = self.equip.item_iter().map(|(_, _, item)| item.w()).sum();
+        items_w + equip_w
+    }
+
+    fn carry_capacity(&self) -> u32 {
+        (self.params.str as u32 + self.params.vit as u32) * RULES.chara.carry_capacity_factor
+    }
+
+    fn update_burden(&mut self) {
+        let r = &RULES.chara;
+        let ratio = self.carrying_w() as f32 / ::std::cmp::max(self.carry_capacity(), 1) as f32;
+        let new_status = if ratio > r.overloaded_ratio {
+            Some(CharaStatus::Overloaded)
+        } else if ratio > r.stressed_ratio {
+            Some(CharaStatus::Stressed)
+        } else if ratio > r.burdened_ratio {
+            Some(CharaStatus::Burdened)
+        } else {
+            None
+        };
+        let old_status = self.status.iter().find(|s| s.about_burden()).cloned();
+        if new_status == old_status { return; }
+
+        if let Some(new_status) = new_status {
+            self.add_status(new_status);
+            game_log!("burden-status"; chara=self, status=new_status);
+        } else {
+            self.remove_burden_status();
+            game_log!("burden-cleared"; chara=self);
+        }
+        update_params(self);
+    }
 }
 
 pub fn damage(game: &mut Game, cid: CharaId, damage: i32, damage_kind: DamageKind) {
@@ -82,10 +171,35 @@ pub fn damage(game: &mut Game, cid: CharaId, damage: i32, damage_kind: DamageKin
             DamageKind::Poison => {
                 game_log!("killed-by-poison-damage"; chara=chara);
             }
+            DamageKind::Bleeding => {
+                game_log!("killed-by-bleeding"; chara=chara);
+            }
+            DamageKind::Burning => {
+                game_log!("killed-by-burning"; chara=chara);
+            }
+            DamageKind::Spell => {
+                game_log!("killed-by-spell"; chara=chara);
+            }
+            DamageKind::Trap => {
+                game_log!("killed-by-trap"; chara=chara);
+            }
         }
     }
 }
 
+/// If the target is killed, the attacker gains exp
+pub fn gain_kill_exp(game: &mut Game, attacker: CharaId, target: CharaId) {
+    let (exp, is_killed) = {
+        let attacker = game.gd.chara.get(attacker);
+        let target = game.gd.chara.get(target);
+        (growth::calc_kill_exp(attacker.base_params.level.level(), target.base_params.level.level()),
+         target.hp < 0)
+    };
+    if is_killed {
+        game.gd.chara.get_mut(attacker).add_base_exp(exp);
+    }
+}
+
 pub fn update_params_by_id(gd: &mut GameData, cid: CharaId) {
     update_params(gd.chara.get_mut(cid));
 }
@@ -100,5 +214,75 @@ pub fn update_params(chara: &mut Chara) {
     chara.params.cha = chara.base_params.cha;
     chara.params.spd = chara.base_params.spd;
     chara.params.view_range = RULES.chara.default_view_range;
+    chara.params.elemental_damage = ElementArray::default();
+    chara.params.resistance = ElementArray::default();
+    chara.params.lifesteal = 0;
+    chara.skills.bonus.clear();
+
+    // Modify params by affixes of equipments
+    for (_, _, item) in chara.equip.item_iter() {
+        for id in item.affixes() {
+            if let Some(affix) = RULES.affix.get(id) {
+                for effect in &affix.effects {
+                    apply_affix_effect(&mut chara.params, &mut chara.skills, effect);
+                }
+            } else {
+                warn!("Unknown affix \"{}\"", id);
+            }
+        }
+    }
+    chara.params.max_mp = (chara.params.int as i32 + chara.params.wil as i32) * RULES.chara.max_mp_factor;
+
+    // Modify params by status
+    for s in chara.status.iter() {
+        match *s {
+            CharaStatus::Blind { strength, .. } => {
+                chara.params.view_range = ::std::cmp::max(1, chara.params.view_range - strength as i32);
+            }
+            CharaStatus::Hasted { strength, .. } => {
+                chara.params.spd = chara.params.spd.saturating_add(strength);
+            }
+            CharaStatus::Burdened => {
+                chara.params.spd = (chara.params.spd as f32 * RULES.chara.burdened_spd_rate) as u16;
+            }
+            CharaStatus::Stressed => {
+                chara.params.spd = (chara.params.spd as f32 * RULES.chara.stressed_spd_rate) as u16;
+            }
+            CharaStatus::Overloaded => {
+                chara.params.spd = (chara.params.spd as f32 * RULES.chara.overloaded_spd_rate) as u16;
+            }
+            _ => (),
+        }
+    }
+}
+
+fn apply_affix_effect(params: &mut CharaParams, skills: &mut SkillList, effect: &AffixEffect) {
+    match *effect {
+        AffixEffect::Attribute { attr, value } => {
+            let p = match attr {
+                AttrKind::Str => &mut params.str,
+                AttrKind::Vit => &mut params.vit,
+                AttrKind::Dex => &mut params.dex,
+                AttrKind::Int => &mut params.int,
+                AttrKind::Wil => &mut params.wil,
+                AttrKind::Cha => &mut params.cha,
+                AttrKind::Spd => &mut params.spd,
+            };
+            *p = p.saturating_add(value);
+        }
+        AffixEffect::ElementalDamage { element, value } => {
+            params.elemental_damage[element] = params.elemental_damage[element].saturating_add(value);
+        }
+        AffixEffect::Resistance { element, value } => {
+            params.resistance[element] = params.resistance[element].saturating_add(value);
+        }
+        AffixEffect::Skill { ref skill, value } => {
+            let bonus = skills.bonus.entry(skill.clone()).or_insert(0);
+            *bonus = bonus.saturating_add(value);
+        }
+        AffixEffect::Lifesteal { percent } => {
+            params.lifesteal = params.lifesteal.saturating_add(percent);
+        }
+    }
 }
 
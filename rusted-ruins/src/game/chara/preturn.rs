@@ -6,10 +6,10 @@ use game::combat::DamageKind;
 use game::extrait::*;
 
 /// This function will be called before the character's turn
-/// 
+///
 pub fn preturn(game: &mut Game, cid: CharaId) -> bool {
-    let mut is_poisoned = false;
-    
+    let mut damages: Vec<(i32, DamageKind)> = Vec::new();
+
     {
         let chara = game.gd.chara.get_mut(cid);
 
@@ -17,31 +17,43 @@ pub fn preturn(game: &mut Game, cid: CharaId) -> bool {
         for s in chara.status.iter_mut() {
             s.advance_turn(1);
         }
-        
+
+        let n_status = chara.status.len();
         chara.status.retain(|s| !s.is_expired()); // Remove expired status
-        
+        if n_status != chara.status.len() {
+            super::update_params(chara);
+        }
+        chara.update_burden();
+
         for s in chara.status.iter() {
             match *s {
-                CharaStatus::Poisoned => {
-                    is_poisoned = true;
+                CharaStatus::Poisoned { strength, .. } => {
+                    let damage = chara.base_params.max_hp * strength as i32 / 100 + 1;
+                    game_log!("poison-damage"; chara=chara, damage=damage);
+                    damages.push((damage, DamageKind::Poison));
+                }
+                CharaStatus::Bleeding { strength, .. } => {
+                    let damage = strength as i32;
+                    game_log!("bleeding-damage"; chara=chara, damage=damage);
+                    damages.push((damage, DamageKind::Bleeding));
+                }
+                CharaStatus::Burning { strength, .. } => {
+                    let damage = strength as i32;
+                    game_log!("burning-damage"; chara=chara, damage=damage);
+                    damages.push((damage, DamageKind::Burning));
                 }
                 _ => (),
             }
         }
     }
 
-    if is_poisoned {
-        let damage = {
-            let chara = game.gd.chara.get_mut(cid);
-            let damage = chara.base_params.max_hp / 20;
-            game_log!("poison-damage"; chara=chara, damage=damage);
-            damage
-        }; // TODO: This block may be unnecessary with NLL
-        super::damage(game, cid, damage, DamageKind::Poison);
+    for (damage, damage_kind) in damages.into_iter() {
+        super::damage(game, cid, damage, damage_kind);
     }
 
     let chara = game.gd.chara.get_mut(cid);
     chara.add_sp(-RULES.chara.sp_consumption, cid);
+    chara.add_mp(RULES.chara.mp_regeneration);
     can_act(chara)
 }
 
@@ -53,6 +65,10 @@ fn can_act(chara: &Chara) -> bool {
                 game_log_i!("asleep"; chara=chara);
                 return false;
             }
+            CharaStatus::Paralyzed { .. } => {
+                game_log_i!("paralyzed"; chara=chara);
+                return false;
+            }
             _ => (),
         }
     }
@@ -1,10 +1,15 @@
 //! Functions for character status operation
 
 use common::gamedata::chara::*;
+use rules::RULES;
 
 pub trait CharaStatusOperation {
     fn add_status(&mut self, new_status: CharaStatus);
+    /// Add status after resistance judgement.
+    /// Returns false if the status is resisted.
+    fn apply_status(&mut self, new_status: CharaStatus) -> bool;
     fn remove_sp_status(&mut self);
+    fn remove_burden_status(&mut self);
 }
 
 impl CharaStatusOperation for Chara {
@@ -14,27 +19,14 @@ impl CharaStatusOperation for Chara {
             CharaStatus::Hungry | CharaStatus::Weak | CharaStatus::Starving => {
                 self.remove_sp_status();
             }
-            CharaStatus::Asleep { turn_left: turn_left_new }=> {
-                for s in self.status.iter_mut() {
-                    match *s {
-                        // Update left sleeping turn
-                        CharaStatus::Asleep { ref mut turn_left } => {
-                            if turn_left_new > *turn_left {
-                                *turn_left = turn_left_new;
-                            }
-                            return;
-                        }
-                        _ => (),
-                    }
-                }
+            CharaStatus::Burdened | CharaStatus::Stressed | CharaStatus::Overloaded => {
+                self.remove_burden_status();
             }
-            CharaStatus::Poisoned => {
+            _ => {
+                // The same kind status is stacked to the existing one.
                 for s in self.status.iter_mut() {
-                    match *s {
-                        CharaStatus::Poisoned => {
-                            return;
-                        }
-                        _ => (),
+                    if s.stack(new_status) {
+                        return;
                     }
                 }
             }
@@ -42,16 +34,55 @@ impl CharaStatusOperation for Chara {
         self.status.push(new_status);
     }
 
+    fn apply_status(&mut self, new_status: CharaStatus) -> bool {
+        let resistance = match new_status.resistance_kind() {
+            Some(StatusResistance::Wil) => self.params.wil,
+            Some(StatusResistance::Vit) => self.params.vit,
+            None => {
+                self.add_status(new_status);
+                return true;
+            }
+        };
+
+        let mut new_status = new_status;
+        let factor = RULES.chara.status_resistance_factor as u32;
+        new_status.modify_turn(|turn_left| {
+            (turn_left as u32 * factor / (factor + resistance as u32)) as u16
+        });
+
+        if new_status.is_expired() {
+            game_log!("resist-status"; chara=self, status=new_status);
+            return false;
+        }
+        self.add_status(new_status);
+        true
+    }
+
     // Remove sp status
     fn remove_sp_status(&mut self) {
         self.status.retain(|s| !s.about_sp());
     }
+
+    // Remove burden status
+    fn remove_burden_status(&mut self) {
+        self.status.retain(|s| !s.about_burden());
+    }
+}
+
+/// The character parameter used for resisting a status
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatusResistance {
+    Wil, Vit,
 }
 
 pub trait CharaStatusEx {
     fn about_sp(&self) -> bool;
+    fn about_burden(&self) -> bool;
     fn advance_turn(&mut self, n: u16);
     fn is_expired(&self) -> bool;
+    fn resistance_kind(&self) -> Option<StatusResistance>;
+    fn modify_turn<F: FnOnce(u16) -> u16>(&mut self, f: F);
+    fn stack(&mut self, other: CharaStatus) -> bool;
 }
 
 impl CharaStatusEx for CharaStatus {
@@ -62,26 +93,93 @@ impl CharaStatusEx for CharaStatus {
         }
     }
 
-    fn advance_turn(&mut self, n: u16) {
+    fn about_burden(&self) -> bool {
         match *self {
-            CharaStatus::Asleep { ref mut turn_left } => {
-                if *turn_left > n {
-                    *turn_left -= n;
-                } else {
-                    *turn_left = 0;
-                }
-            }
-            _ => (),
+            CharaStatus::Burdened | CharaStatus::Stressed | CharaStatus::Overloaded => true,
+            _ => false,
         }
     }
 
+    fn advance_turn(&mut self, n: u16) {
+        self.modify_turn(|turn_left| turn_left.saturating_sub(n));
+    }
+
     /// If this status is expired, returns true.
     /// Expired status will be removed from character.
     fn is_expired(&self) -> bool {
         match *self {
-            CharaStatus::Asleep { turn_left } if turn_left == 0 => true,
+            CharaStatus::Hungry | CharaStatus::Weak | CharaStatus::Starving => false,
+            CharaStatus::Burdened | CharaStatus::Stressed | CharaStatus::Overloaded => false,
+            CharaStatus::Asleep { turn_left } |
+            CharaStatus::Poisoned { turn_left, .. } |
+            CharaStatus::Confused { turn_left, .. } |
+            CharaStatus::Paralyzed { turn_left } |
+            CharaStatus::Blind { turn_left, .. } |
+            CharaStatus::Bleeding { turn_left, .. } |
+            CharaStatus::Burning { turn_left, .. } |
+            CharaStatus::Hasted { turn_left, .. } => turn_left == 0,
+        }
+    }
+
+    /// Mental status is resisted by wil, and physical status is resisted by vit.
+    /// Returns None for the status that cannot be resisted.
+    fn resistance_kind(&self) -> Option<StatusResistance> {
+        match *self {
+            CharaStatus::Asleep { .. } |
+            CharaStatus::Confused { .. } |
+            CharaStatus::Paralyzed { .. } => Some(StatusResistance::Wil),
+            CharaStatus::Poisoned { .. } |
+            CharaStatus::Blind { .. } |
+            CharaStatus::Bleeding { .. } |
+            CharaStatus::Burning { .. } => Some(StatusResistance::Vit),
+            _ => None,
+        }
+    }
+
+    fn modify_turn<F: FnOnce(u16) -> u16>(&mut self, f: F) {
+        match *self {
+            CharaStatus::Hungry | CharaStatus::Weak | CharaStatus::Starving => (),
+            CharaStatus::Burdened | CharaStatus::Stressed | CharaStatus::Overloaded => (),
+            CharaStatus::Asleep { ref mut turn_left } |
+            CharaStatus::Poisoned { ref mut turn_left, .. } |
+            CharaStatus::Confused { ref mut turn_left, .. } |
+            CharaStatus::Paralyzed { ref mut turn_left } |
+            CharaStatus::Blind { ref mut turn_left, .. } |
+            CharaStatus::Bleeding { ref mut turn_left, .. } |
+            CharaStatus::Burning { ref mut turn_left, .. } |
+            CharaStatus::Hasted { ref mut turn_left, .. } => {
+                *turn_left = f(*turn_left);
+            }
+        }
+    }
+
+    /// If other is the same kind status, update turn and strength by larger values.
+    /// Returns true if stacked.
+    fn stack(&mut self, other: CharaStatus) -> bool {
+        use std::cmp::max;
+        match (self, other) {
+            (&mut CharaStatus::Asleep { ref mut turn_left }, CharaStatus::Asleep { turn_left: t }) |
+            (&mut CharaStatus::Paralyzed { ref mut turn_left }, CharaStatus::Paralyzed { turn_left: t }) => {
+                *turn_left = max(*turn_left, t);
+                true
+            }
+            (&mut CharaStatus::Poisoned { ref mut turn_left, ref mut strength },
+             CharaStatus::Poisoned { turn_left: t, strength: st }) |
+            (&mut CharaStatus::Confused { ref mut turn_left, ref mut strength },
+             CharaStatus::Confused { turn_left: t, strength: st }) |
+            (&mut CharaStatus::Blind { ref mut turn_left, ref mut strength },
+             CharaStatus::Blind { turn_left: t, strength: st }) |
+            (&mut CharaStatus::Bleeding { ref mut turn_left, ref mut strength },
+             CharaStatus::Bleeding { turn_left: t, strength: st }) |
+            (&mut CharaStatus::Burning { ref mut turn_left, ref mut strength },
+             CharaStatus::Burning { turn_left: t, strength: st }) |
+            (&mut CharaStatus::Hasted { ref mut turn_left, ref mut strength },
+             CharaStatus::Hasted { turn_left: t, strength: st }) => {
+                *turn_left = max(*turn_left, t);
+                *strength = max(*strength, st);
+                true
+            }
             _ => false,
         }
     }
 }
-
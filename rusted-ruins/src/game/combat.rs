@@ -2,6 +2,8 @@
 use rng;
 use super::Game;
 use super::chara::CharaEx;
+use super::item::ItemEx;
+use super::item::durability::{wear_weapon, wear_armor};
 use common::gobj;
 use common::gamedata::*;
 use rules::RULES;
@@ -10,6 +12,10 @@ pub enum DamageKind {
     MeleeAttack,
     RangedAttack,
     Poison,
+    Bleeding,
+    Burning,
+    Spell,
+    Trap,
 }
 
 /// Attack neighbor enemy by short range weapon or martial arts
@@ -28,20 +34,21 @@ pub fn attack_neighbor(game: &mut Game, attacker: CharaId, target: CharaId) {
             let weapon_kind = get_weapon_kind(weapon_obj);
             skill_kind = SkillKind::Weapon(weapon_kind);
             
-            let dice_result = rng::dice(weapon_obj.dice_n as i32, weapon_obj.dice_x as i32);
-            let weapon_skill_level = attacker.skills.get(skill_kind);
+            let (dice_n, dice_x) = weapon.dice();
+            let dice_result = rng::dice(dice_n as i32, dice_x as i32);
+            let weapon_skill_level = attacker.skills.get(&skill_kind);
             let attack_power = calc_attack_power(dice_result, attacker.params.str, weapon_skill_level);
-            let defence_skill_level = target.skills.get(SkillKind::Defence);
+            let defence_skill_level = target.skills.get(&SkillKind::Defence);
             let defence_power = calc_defence_power(equip_def[Element::Physical], target.params.vit, defence_skill_level);
-            (attack_power / defence_power) as i32
+            apply_elements(attacker, target, (attack_power / defence_power) as i32)
         } else { // Attack by martial arts
             skill_kind = SkillKind::MartialArts;
-            let weapon_skill_level = attacker.skills.get(skill_kind);
+            let weapon_skill_level = attacker.skills.get(&skill_kind);
             let dice_result = rng::dice(1, weapon_skill_level as i32 / 3 + 1);
             let attack_power = calc_attack_power(dice_result, attacker.params.str, weapon_skill_level);
-            let defence_skill_level = target.skills.get(SkillKind::Defence);
+            let defence_skill_level = target.skills.get(&SkillKind::Defence);
             let defence_power = calc_defence_power(equip_def[Element::Physical], target.params.vit, defence_skill_level);
-            (attack_power / defence_power) as i32
+            apply_elements(attacker, target, (attack_power / defence_power) as i32)
         }
     };
     // Logging
@@ -50,11 +57,18 @@ pub fn attack_neighbor(game: &mut Game, attacker: CharaId, target: CharaId) {
         let target = game.gd.chara.get(target);
         game_log!("attack"; attacker=attacker, target=target, damage=damage);
     }
+    // Equipment damage
+    if damage > 0 {
+        wear_weapon(&mut game.gd, attacker, EquipSlotKind::MeleeWeapon);
+        wear_armor(&mut game.gd, target);
+    }
     // Damage processing
     super::chara::damage(game, target, damage, DamageKind::MeleeAttack);
+    lifesteal(game, attacker, damage);
+    super::chara::gain_kill_exp(game, attacker, target);
     // Exp processing
     {
-        let target_level = game.gd.chara.get(target).base_params.level;
+        let target_level = game.gd.chara.get(target).base_params.level.level();
         let attacker = game.gd.chara.get_mut(attacker);
         attacker.add_skill_exp(
             skill_kind,
@@ -86,13 +100,14 @@ pub fn shot_target(game: &mut Game, attacker: CharaId, target: CharaId) -> bool
         };
         let weapon_obj = gobj::get_obj(weapon.idx);
         let weapon_kind = get_weapon_kind(weapon_obj);
-        let dice_result = rng::dice(weapon_obj.dice_n as i32, weapon_obj.dice_x as i32);
+        let (dice_n, dice_x) = weapon.dice();
+        let dice_result = rng::dice(dice_n as i32, dice_x as i32);
         
-        let weapon_skill_level = attacker.skills.get(SkillKind::Weapon(weapon_kind));
+        let weapon_skill_level = attacker.skills.get(&SkillKind::Weapon(weapon_kind));
         let attack_power = calc_attack_power(dice_result, attacker.params.dex, weapon_skill_level);
-        let defence_skill_level = target.skills.get(SkillKind::Defence);
+        let defence_skill_level = target.skills.get(&SkillKind::Defence);
         let defence_power = calc_defence_power(equip_def[Element::Physical], target.params.vit, defence_skill_level);
-        let damage = (attack_power / defence_power) as i32;
+        let damage = apply_elements(attacker, target, (attack_power / defence_power) as i32);
         
         (damage, weapon_kind, attacker_pos, target_pos)
     };
@@ -102,11 +117,18 @@ pub fn shot_target(game: &mut Game, attacker: CharaId, target: CharaId) -> bool
         let target = game.gd.chara.get(target);
         game_log!("shot-target"; attacker=attacker, target=target, damage=damage);
     }
+    // Equipment damage
+    if damage > 0 {
+        wear_weapon(&mut game.gd, attacker, EquipSlotKind::RangedWeapon);
+        wear_armor(&mut game.gd, target);
+    }
     // Damage processing
     super::chara::damage(game, target, damage, DamageKind::RangedAttack);
+    lifesteal(game, attacker, damage);
+    super::chara::gain_kill_exp(game, attacker, target);
     // Exp processing
     {
-        let target_level = game.gd.chara.get(target).base_params.level;
+        let target_level = game.gd.chara.get(target).base_params.level.level();
         let attacker = game.gd.chara.get_mut(attacker);
         attacker.add_skill_exp(
             SkillKind::Weapon(weapon_kind),
@@ -127,14 +149,42 @@ fn get_weapon_kind(item: &ItemObject) -> WeaponKind {
     }
 }
 
+/// Add elemental damage of the attacker's equipments,
+/// and reduce damage by the target's resistances
+fn apply_elements(attacker: &Chara, target: &Chara, physical_damage: i32) -> i32 {
+    let mut damage = reduce_by_resistance(target, Element::Physical, physical_damage);
+    for e in &ELEMENTS {
+        if *e == Element::Physical { continue; }
+        let elemental_damage = attacker.params.elemental_damage[*e] as i32;
+        if elemental_damage > 0 {
+            damage += reduce_by_resistance(target, *e, elemental_damage);
+        }
+    }
+    damage
+}
+
+fn reduce_by_resistance(target: &Chara, element: Element, damage: i32) -> i32 {
+    let resistance = ::std::cmp::min(target.params.resistance[element], 100) as i32;
+    damage * (100 - resistance) / 100
+}
+
+/// Recover attacker's hp by inflicted damage
+fn lifesteal(game: &mut Game, attacker: CharaId, damage: i32) {
+    let chara = game.gd.chara.get_mut(attacker);
+    if chara.params.lifesteal == 0 || damage <= 0 || chara.hp <= 0 { return; }
+
+    let heal = damage * chara.params.lifesteal as i32 / 100;
+    chara.hp = ::std::cmp::min(chara.params.max_hp, chara.hp + heal);
+}
+
 /// Calculate character's defence for each elements
 fn calc_equip_defence(gd: &GameData, cid: CharaId) -> ElementArray<u16> {
     let mut def: ElementArray<u16> = ElementArray::default();
     
     for (_, _, item) in gd.get_equip_list(cid).item_iter() {
-        let item_obj: &ItemObject = gobj::get_obj(item.idx);
+        let item_def = item.def();
         for e in &ELEMENTS {
-            def[*e] = def[*e].saturating_add(item_obj.def[*e]);
+            def[*e] = def[*e].saturating_add(item_def[*e]);
         }
     }
 
@@ -10,9 +10,14 @@ pub enum Command {
     Enter,
     Cancel,
     RotateWindowRight, RotateWindowLeft,
+    SellJunk,
+    SortItems, SplitItem, MarkFavorite, MarkJunk,
     Shot,
     OpenExitWin, OpenItemMenu, OpenEquipWin, OpenStatusWin, OpenGameInfoWin,
     PickUpItem, DropItem, DrinkItem, EatItem,
+    Cast,
+    Craft,
+    CloseDoor,
     TargetingMode,
     TextInput { text: String },
     TextDelete,
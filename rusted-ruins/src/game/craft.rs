@@ -0,0 +1,139 @@
+
+//! Functions for crafting items by recipes
+
+use array2d::*;
+use common::gamedata::*;
+use common::gobj;
+use common::objholder::{ItemIdx, RecipeIdx};
+use common::recipe::RecipeObject;
+use game::extrait::*;
+use game::InfoGetter;
+use rng;
+use rules::RULES;
+use super::item::gen::gen_item_from_idx;
+
+/// Recipes which the player can try. The player needs to have learned the skill.
+pub fn available_recipes(gd: &GameData) -> Vec<RecipeIdx> {
+    let player = gd.chara.get(CharaId::Player);
+    gobj::get_objholder().recipe.iter().enumerate()
+        .filter(|&(_, recipe)| player.skills.get(&recipe.skill) > 0)
+        .map(|(i, _)| RecipeIdx(i as u32))
+        .collect()
+}
+
+/// The number of items of the given kind in the list
+fn count_item(list: &ItemList, idx: ItemIdx) -> u32 {
+    list.iter().filter(|&&(ref item, _)| is_input(item, idx)).map(|&(_, n)| n).sum()
+}
+
+/// Favorite items are not used as inputs
+fn is_input(item: &Item, idx: ItemIdx) -> bool {
+    item.idx == idx && !item.flags.contains(ItemFlags::FAVORITE)
+}
+
+/// Returns true if the player has all input items
+pub fn has_inputs(gd: &GameData, recipe: &RecipeObject) -> bool {
+    let list = gd.get_item_list(ItemListLocation::Chara { cid: CharaId::Player });
+    recipe.inputs.iter().all(|input| {
+        if let Some(idx) = gobj::id_to_idx_checked::<ItemIdx>(&input.id) {
+            count_item(list, idx) >= input.n
+        } else {
+            false
+        }
+    })
+}
+
+/// Returns true if the workstation is on or next to the player's tile
+pub fn near_workstation(gd: &GameData, workstation: &str) -> bool {
+    let map = gd.get_current_map();
+    let center = gd.player_pos();
+    let mut dirs = Direction::EIGHT_DIRS.iter().map(|dir| dir.as_vec()).collect::<Vec<_>>();
+    dirs.push(Vec2d::new(0, 0));
+
+    dirs.into_iter().map(|d| center + d).filter(|&p| map.is_inside(p)).any(|p| {
+        let tile = &map.tile[p];
+        let is_deco = tile.deco.map(|deco| gobj::idx_to_id(deco) == workstation).unwrap_or(false);
+        is_deco || tile.special.obj_id() == Some(workstation)
+    })
+}
+
+/// Consume n items of the given kind from the player's inventory.
+/// Unmarked plain items are consumed first. Favorites are never consumed.
+fn consume_item(gd: &mut GameData, idx: ItemIdx, mut n: u32) {
+    let list = gd.get_item_list_mut(ItemListLocation::Chara { cid: CharaId::Player });
+    while n > 0 {
+        let i = if let Some((i, _)) = list.iter().enumerate()
+            .filter(|&(_, &(ref item, _))| is_input(item, idx))
+            .min_by_key(|&(_, &(ref item, _))| {
+                (item.flags.contains(ItemFlags::JUNK), !item.affixes().is_empty(), item.rank.as_int())
+            }) {
+            i
+        } else {
+            break;
+        };
+        let m = ::std::cmp::min(list.items[i].1, n);
+        list.remove(i as u32, m);
+        n -= m;
+    }
+}
+
+/// The player crafts items by the recipe.
+/// Input items are consumed even if the crafting fails.
+/// Returns false if the player cannot try this recipe.
+pub fn craft(gd: &mut GameData, recipe_idx: RecipeIdx) -> bool {
+    let recipe: &RecipeObject = gobj::get_obj(recipe_idx);
+    let output_idx = if let Some(idx) = gobj::id_to_idx_checked::<ItemIdx>(&recipe.output) {
+        idx
+    } else {
+        warn!("Unknown item id \"{}\" in recipe \"{}\"", recipe.output, recipe.id);
+        return false;
+    };
+
+    if let Some(ref workstation) = recipe.workstation {
+        if !near_workstation(gd, workstation) {
+            let workstation = ::text::obj_txt(workstation);
+            game_log_i!("craft-no-workstation"; workstation=workstation);
+            return false;
+        }
+    }
+    if !has_inputs(gd, recipe) {
+        game_log_i!("craft-lack-of-inputs");
+        return false;
+    }
+
+    for input in &recipe.inputs {
+        consume_item(gd, gobj::id_to_idx(&input.id), input.n);
+    }
+
+    let r = &RULES.craft;
+    let level_diff = gd.chara.get(CharaId::Player).skills.get(&recipe.skill) as f32
+        - recipe.difficulty as f32;
+    let chance = r.base_success_chance + level_diff * r.success_chance_per_level;
+    let chance = if chance < r.min_success_chance { r.min_success_chance } else { chance };
+
+    if rng::gen_range(0.0, 1.0) >= chance {
+        game_log_i!("craft-failure"; chara=gd.chara.get(CharaId::Player));
+        return true;
+    }
+
+    let mut item = gen_item_from_idx(output_idx, gobj::get_obj(output_idx).gen_level);
+    item.rank.base = quality_roll(level_diff);
+    game_log_i!("craft-success"; chara=gd.chara.get(CharaId::Player), item=item);
+    gd.get_item_list_mut(ItemListLocation::Chara { cid: CharaId::Player })
+        .append(item, recipe.n_output);
+
+    if let Some(params) = recipe.skill.non_combat_id().and_then(|id| RULES.skill.get(id)) {
+        gd.chara.get_mut(CharaId::Player).add_skill_exp(recipe.skill.clone(), params.exp, recipe.difficulty);
+    }
+    gd.chara.get_mut(CharaId::Player).update_burden();
+    true
+}
+
+/// Decide the base rank of the crafted item.
+/// Higher skill level than the recipe difficulty gives better quality.
+fn quality_roll(level_diff: f32) -> i8 {
+    let max = RULES.craft.max_quality as f32;
+    let q = (level_diff * RULES.craft.quality_per_level + rng::gen_range(-1.0, 1.0)).round();
+    let q = if q > max { max } else if q < -max { -max } else { q };
+    q as i8
+}
@@ -0,0 +1,144 @@
+
+//! Estimate the danger of maps from characters and items on them
+
+use array2d::Vec2d;
+use common::gamedata::*;
+use common::gobj;
+use super::dungeon_gen::N_NPC_PER_FLOOR;
+use super::chara::creation::expected_npc_level;
+
+/// Items whose level exceeds the floor level by this margin are notable
+const NOTABLE_ITEM_LEVEL_MARGIN: i32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum DangerLevel {
+    Safe, Low, Moderate, High, Deadly,
+}
+
+impl DangerLevel {
+    fn from_score(score: f64) -> DangerLevel {
+        if score < 3.0 {
+            DangerLevel::Safe
+        } else if score < 8.0 {
+            DangerLevel::Low
+        } else if score < 15.0 {
+            DangerLevel::Moderate
+        } else if score < 30.0 {
+            DangerLevel::High
+        } else {
+            DangerLevel::Deadly
+        }
+    }
+
+    /// Log text id to describe the feeling when entering a floor
+    pub fn feeling_log_id(&self) -> &'static str {
+        match *self {
+            DangerLevel::Safe => "level-feeling-safe",
+            DangerLevel::Low => "level-feeling-low",
+            DangerLevel::Moderate => "level-feeling-moderate",
+            DangerLevel::High => "level-feeling-high",
+            DangerLevel::Deadly => "level-feeling-deadly",
+        }
+    }
+}
+
+/// The danger of one floor
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FloorDanger {
+    pub level: DangerLevel,
+    /// Items better than ones usually found on this floor exist
+    pub notable_items: bool,
+}
+
+/// Estimate the danger of the map from hostile characters' levels relative to the player,
+/// and items' levels relative to the floor level.
+pub fn estimate_map_danger(gd: &GameData, mid: MapId) -> FloorDanger {
+    let map = gd.region.get_map(mid);
+    let player = gd.chara.get(CharaId::Player);
+    let player_level = player.base_params.level.level() as i32;
+
+    let score: f64 = map.iter_charaid()
+        .filter(|&&cid| cid != CharaId::Player)
+        .map(|&cid| gd.chara.get(cid))
+        .filter(|chara| player.rel.relative(chara.rel) == Relationship::HOSTILE)
+        .map(|chara| level_diff_score(chara.base_params.level.level() as i32 - player_level))
+        .sum();
+
+    let floor_level = mid.floor() as i32;
+    let notable_items = map.tile.iter()
+        .filter_map(|tile| tile.item_list.as_ref())
+        .flat_map(|list| list.iter())
+        .any(|&(ref item, _)| {
+            let item_level = gobj::get_obj(item.idx).gen_level as i32 + item.rank.as_int();
+            item_level >= floor_level + NOTABLE_ITEM_LEVEL_MARGIN
+        });
+
+    FloorDanger {
+        level: DangerLevel::from_score(score),
+        notable_items,
+    }
+}
+
+/// Estimate the danger of the site for the player outside of it.
+/// Loaded floors are evaluated by their characters.
+/// If the maps are unloaded or not generated yet, the deepest floor is estimated
+/// from the dungeon kind and the floor level.
+/// Returns None if the site is not a dungeon.
+pub fn estimate_site_danger(gd: &GameData, sid: SiteId) -> Option<DangerLevel> {
+    let site = gd.region.get_site(sid);
+    let dungeon_kind = match site.content {
+        SiteContent::AutoGenDungeon { dungeon_kind, .. } => dungeon_kind,
+        _ => { return None; }
+    };
+
+    if !site.is_unloaded() && site.floor_num() > 0 {
+        let level = (0..site.floor_num())
+            .map(|floor| estimate_map_danger(gd, MapId::SiteMap { sid, floor }).level)
+            .max()
+            .unwrap();
+        return Some(level);
+    }
+
+    let floor_level = site.floor_num().saturating_sub(1);
+    let npc_level = expected_npc_level(dungeon_kind, floor_level);
+    let player_level = gd.chara.get(CharaId::Player).base_params.level.level() as f64;
+    let score = N_NPC_PER_FLOOR as f64 * level_diff_score_f64(npc_level - player_level);
+    Some(DangerLevel::from_score(score))
+}
+
+/// Characters of higher level than the player are much more dangerous
+fn level_diff_score(level_diff: i32) -> f64 {
+    level_diff_score_f64(level_diff as f64)
+}
+
+fn level_diff_score_f64(level_diff: f64) -> f64 {
+    2.0f64.powf(level_diff / 3.0)
+}
+
+/// Log the danger of the current floor if it is a dungeon floor
+pub fn report_floor_danger(gd: &GameData) {
+    let mid = gd.get_current_mapid();
+    if mid.is_region_map() || mid.sid().kind != SiteKind::AutoGenDungeon {
+        return;
+    }
+
+    let danger = estimate_map_danger(gd, mid);
+    game_log_i!(danger.level.feeling_log_id());
+    if danger.notable_items {
+        game_log_i!("level-feeling-treasure");
+    }
+}
+
+/// Log the danger of the site if its symbol is at the given position on the region map
+pub fn report_site_danger(gd: &GameData, pos: Vec2d) {
+    let mid = gd.get_current_mapid();
+    if !mid.is_region_map() {
+        return;
+    }
+
+    let sid = if let Some(sid) = gd.region.get(mid.rid()).get_id_by_pos(pos) { sid } else { return; };
+    if let Some(danger) = estimate_site_danger(gd, sid) {
+        let site = gd.region.get_site(sid);
+        game_log_i!("site-danger"; site=site, danger=danger);
+    }
+}
@@ -1,18 +1,30 @@
 //! This module provides functions for auto generated dungeons
 
 use array2d::*;
-use rng;
+use rng::{self, Rng};
 use common::gamedata::*;
 use common::objholder::*;
+use common::maptemplate::MapTemplateObject;
 use common::gobj;
 use game::map::builder::MapBuilder;
+use game::item::gen::gen_dungeon_item_from_pool;
+use game::map::{choose_empty_tile, is_tile_empty};
+use game::map::from_template;
+use game::map::prefab::stitch_prefab;
+use game::chara::creation::{create_npc_chara, create_hostile_npc_chara};
+use map_generator::GeneratedMap;
 use rules::RULES;
+use rules::dungeon_gen::{TerrainSet, DungeonTheme};
+
+/// The number of npcs generated on each floor
+pub const N_NPC_PER_FLOOR: u32 = 10;
 
 /// Add a new dungeon
 pub fn add_dungeon_site(gd: &mut GameData, dungeon_kind: DungeonKind, pos: Vec2d) -> SiteId {
     let floor_range = &RULES.dungeon_gen[&dungeon_kind].floor_range;
     let mut site = Site::new(rng::gen_range(floor_range[0], floor_range[1]));
-    site.content = SiteContent::AutoGenDungeon { dungeon_kind };
+    let theme = choose_theme(dungeon_kind).map(|theme| theme.id.clone());
+    site.content = SiteContent::AutoGenDungeon { dungeon_kind, theme };
     let sid = gd.add_site(site, SiteKind::AutoGenDungeon, RegionId::default(), pos).unwrap();
     extend_site_floor(gd, sid);
     
@@ -23,17 +35,30 @@ pub fn add_dungeon_site(gd: &mut GameData, dungeon_kind: DungeonKind, pos: Vec2d
 pub fn extend_site_floor(gd: &mut GameData, sid: SiteId) {
     let floor = gd.region.get_site(sid).floor_num();
     let is_deepest_floor = floor >= gd.region.get_site(sid).max_floor() - 1;
-    let map = match gd.region.get_site(sid).content {
-        SiteContent::AutoGenDungeon { dungeon_kind } => {
+    let theme = get_theme(gd, sid);
+    let (map, mut layout) = match gd.region.get_site(sid).content {
+        SiteContent::AutoGenDungeon { dungeon_kind, .. } => {
             let map_size = RULES.dungeon_gen[&dungeon_kind].map_size;
-            let tile_idx = gobj::id_to_idx(&RULES.dungeon_gen[&dungeon_kind].terrain[0][0]);
-            let wall_idx = gobj::id_to_idx(&RULES.dungeon_gen[&dungeon_kind].terrain[0][1]);
-            MapBuilder::new(map_size.0 as u32, map_size.1 as u32)
+            let terrain = choose_terrain_set(dungeon_kind, floor);
+            let tile_idx = gobj::id_to_idx(&terrain.tile);
+            let wall_idx = gobj::id_to_idx(&terrain.wall);
+            let deco: Vec<DecoIdx> = terrain.deco.iter()
+                .filter_map(|id| checked_idx(id))
+                .collect();
+            let mut builder = MapBuilder::new(map_size.0 as u32, map_size.1 as u32)
                 .floor(floor)
                 .tile(tile_idx)
                 .wall(wall_idx)
-                .deepest_floor(is_deepest_floor)
-                .build()
+                .deco(deco, terrain.deco_density)
+                .map_gen(theme.and_then(|theme| theme.map_gen.clone())
+                         .unwrap_or_else(|| RULES.dungeon_gen[&dungeon_kind].map_gen.clone()))
+                .deepest_floor(is_deepest_floor);
+            for layer in &terrain.layers {
+                if let Some(idx) = checked_idx(&layer.tile) {
+                    builder = builder.tile_layer(idx, layer.ratio);
+                }
+            }
+            builder.build()
         }
         _ => {
             MapBuilder::new(40, 40).floor(floor).build()
@@ -41,29 +66,282 @@ pub fn extend_site_floor(gd: &mut GameData, sid: SiteId) {
     };
     
     let mid = gd.add_map(map, sid);
-    super::map::gen_npcs(gd, mid, 10, mid.floor());
+    if let SiteContent::AutoGenDungeon { dungeon_kind, .. } = gd.region.get_site(sid).content {
+        gen_prefabs(gd, mid, dungeon_kind, &mut layout);
+        gen_room_features(gd, mid, dungeon_kind, &layout);
+        gen_special_tiles(gd, mid, dungeon_kind, &layout);
+    }
+    super::map::gen_npcs(gd, mid, N_NPC_PER_FLOOR, mid.floor());
     super::map::gen_items(gd, mid);
     
     if is_deepest_floor {
         add_for_deepest_floor(gd, mid);
     }
+    super::item::identify::hide_unknown_items_on_map(gd, mid);
 }
 
-/// Add items for deepest floor of dungeon
+/// Choose one theme by weight
+fn choose_theme(dungeon_kind: DungeonKind) -> Option<&'static DungeonTheme> {
+    let themes = &RULES.dungeon_gen[&dungeon_kind].themes;
+    let sum: f32 = themes.iter().map(|t| t.weight).sum();
+    if sum <= 0.0 {
+        return None;
+    }
+
+    let mut r = rng::gen_range(0.0, sum);
+    for t in themes {
+        if r < t.weight {
+            return Some(t);
+        }
+        r -= t.weight;
+    }
+    themes.last()
+}
+
+/// Get the theme of the dungeon site
+pub fn get_theme(gd: &GameData, sid: SiteId) -> Option<&'static DungeonTheme> {
+    match gd.region.get_site(sid).content {
+        SiteContent::AutoGenDungeon { dungeon_kind, theme: Some(ref theme) } => {
+            let t = RULES.dungeon_gen[&dungeon_kind].theme(theme);
+            if t.is_none() {
+                warn!("Unknown dungeon theme \"{}\"", theme);
+            }
+            t
+        }
+        _ => None,
+    }
+}
+
+/// Items in the theme's item pool
+fn theme_items(gd: &GameData, sid: SiteId) -> &'static [String] {
+    get_theme(gd, sid).map_or(&[], |theme| &theme.items)
+}
+
+/// Choose one terrain set available on the floor by weight
+fn choose_terrain_set(dungeon_kind: DungeonKind, floor: u32) -> &'static TerrainSet {
+    let terrain = &RULES.dungeon_gen[&dungeon_kind].terrain;
+    let available: Vec<&TerrainSet> = terrain.iter().filter(|t| t.is_available(floor)).collect();
+    if available.is_empty() {
+        warn!("No terrain set is available on floor {} of {:?}", floor, dungeon_kind);
+        return &terrain[0];
+    }
+
+    let sum: f32 = available.iter().map(|t| t.weight).sum();
+    if sum <= 0.0 {
+        warn!("Weights of terrain sets on floor {} of {:?} are zero", floor, dungeon_kind);
+        return available[0];
+    }
+    let mut r = rng::gen_range(0.0, sum);
+    for t in &available {
+        if r < t.weight {
+            return t;
+        }
+        r -= t.weight;
+    }
+    available[available.len() - 1]
+}
+
+fn checked_idx<T: ObjectIndex>(id: &str) -> Option<T> {
+    let idx = gobj::id_to_idx_checked(id);
+    if idx.is_none() {
+        warn!("Unknown object id \"{}\" in dungeon generation rules", id);
+    }
+    idx
+}
+
+/// Stitch prefabs into the floor.
+/// The rooms used by prefabs are removed from the layout.
+fn gen_prefabs(gd: &mut GameData, mid: MapId, dungeon_kind: DungeonKind, layout: &mut GeneratedMap) {
+    let floor = mid.floor();
+
+    for prefab in &RULES.dungeon_gen[&dungeon_kind].prefabs {
+        if let Some(floor_range) = prefab.floor_range {
+            if floor < floor_range[0] || floor_range[1] < floor { continue; }
+        }
+        if !rng::get_rng().gen_bool(prefab.probability) { continue; }
+
+        let t: &MapTemplateObject = if let Some(t) = gobj::get_by_id_checked(&prefab.id) {
+            t
+        } else {
+            warn!("Unknown map template \"{}\" for prefab", prefab.id);
+            continue;
+        };
+
+        let top_left = if let Some(p) = stitch_prefab(gd.region.get_map_mut(mid), layout, t) {
+            p
+        } else {
+            continue;
+        };
+        from_template::gen_charas(gd, mid, t, top_left);
+
+        let right_bottom = top_left + (t.w as i32 - 1, t.h as i32 - 1);
+        layout.rooms.retain(|room| {
+            room.right_bottom.0 < top_left.0 || right_bottom.0 < room.top_left.0 ||
+                room.right_bottom.1 < top_left.1 || right_bottom.1 < room.top_left.1
+        });
+    }
+}
+
+/// Make some rooms vaults, treasure rooms or monster nests,
+/// and locate some items at dead ends.
+fn gen_room_features(gd: &mut GameData, mid: MapId, dungeon_kind: DungeonKind, layout: &GeneratedMap) {
+    let params = &RULES.dungeon_gen[&dungeon_kind].room_feature;
+    let floor = mid.floor();
+    let items = theme_items(gd, mid.sid());
+
+    for room in &layout.rooms {
+        // Rooms with stairs are not changed
+        if room.contains(layout.entrance) || layout.exit.map_or(false, |p| room.contains(p)) {
+            continue;
+        }
+        let tiles: Vec<Vec2d> = room.iter().collect();
+
+        let r = rng::gen_range(0.0, 1.0);
+        if r < params.vault {
+            // Vault is closed by locked doors, and has treasures
+            let exits = layout.room_exits(room);
+            if exits.is_empty() || exits.len() > params.vault_max_exits as usize { continue; }
+            let lock_level = floor as u16 + params.vault_lock_level;
+            let map = gd.region.get_map_mut(mid);
+            for &p in &exits {
+                if map.tile[p].special.is_none() {
+                    map.tile[p].special = SpecialTileKind::Door { open: false, lock_level };
+                }
+            }
+            // A lever outside the vault opens one of the doors
+            if rng::get_rng().gen_bool(params.vault_lever) {
+                let target = *rng::get_rng().choose(&exits).unwrap();
+                let outside: Vec<Vec2d> = map.tile.iter_idx().filter(|&p| !room.contains(p)).collect();
+                if let Some(p) = choose_empty_tile_in(map, &outside) {
+                    map.tile[p].special = SpecialTileKind::Lever { on: false, target };
+                }
+            }
+            locate_items_in(map, &tiles, params.n_treasure, floor, items);
+        } else if r < params.vault + params.treasure_room {
+            let map = gd.region.get_map_mut(mid);
+            locate_items_in(map, &tiles, params.n_treasure, floor, items);
+        } else if r < params.vault + params.treasure_room + params.monster_nest {
+            let n = rng::gen_range(params.n_nest_monster[0], params.n_nest_monster[1] + 1);
+            for _ in 0..n {
+                let p = if let Some(p) = choose_empty_tile_in(gd.region.get_map(mid), &tiles) {
+                    p
+                } else {
+                    break;
+                };
+                let chara = create_npc_chara(dungeon_kind, floor);
+                let cid = gd.add_chara_to_map(chara, mid);
+                gd.region.get_map_mut(mid).locate_chara(cid, p);
+            }
+        }
+    }
+
+    let map = gd.region.get_map_mut(mid);
+    for &p in &layout.dead_ends {
+        if rng::get_rng().gen_bool(params.dead_end_item) && is_tile_empty(&map.tile[p]) {
+            map.locate_item(gen_dungeon_item_from_pool(floor, items), p, 1);
+        }
+    }
+}
+
+/// Locate items at random tiles in the given tiles
+fn locate_items_in(map: &mut Map, tiles: &[Vec2d], n: [u32; 2], floor: u32, items: &[String]) {
+    for _ in 0..rng::gen_range(n[0], n[1] + 1) {
+        if let Some(p) = choose_empty_tile_in(map, tiles) {
+            map.locate_item(gen_dungeon_item_from_pool(floor, items), p, 1);
+        }
+    }
+}
+
+/// Choose one empty tile in the given tiles
+fn choose_empty_tile_in(map: &Map, tiles: &[Vec2d]) -> Option<Vec2d> {
+    let empty_tiles: Vec<Vec2d> = tiles.iter()
+        .filter(|&&p| map.is_inside(p) && is_tile_empty(&map.tile[p]))
+        .cloned()
+        .collect();
+    rng::get_rng().choose(&empty_tiles).cloned()
+}
+
+/// Locate traps and containers, and lock doors.
+/// The number of traps and their kinds depend on the floor level.
+fn gen_special_tiles(gd: &mut GameData, mid: MapId, dungeon_kind: DungeonKind, layout: &GeneratedMap) {
+    let params = &RULES.dungeon_gen[&dungeon_kind];
+    let floor = mid.floor();
+    let lock_level = floor as u16 + 1;
+    let items = theme_items(gd, mid.sid());
+    let map = gd.region.get_map_mut(mid);
+
+    // Lock doors. Doors already locked such as vault doors are kept.
+    for p in map.tile.iter_idx() {
+        if let SpecialTileKind::Door { open, lock_level: 0 } = map.tile[p].special {
+            if rng::get_rng().gen_bool(params.lock_probability) {
+                map.tile[p].special = SpecialTileKind::Door { open, lock_level };
+            }
+        }
+    }
+
+    // Traps
+    let trap_kinds: Vec<TrapKind> = params.trap.kinds.iter()
+        .filter(|&&(_, min_floor)| min_floor <= floor)
+        .map(|&(kind, _)| kind)
+        .collect();
+    if !trap_kinds.is_empty() {
+        let n_trap = (params.trap.n_base + params.trap.n_per_floor * floor as f32) as u32;
+        // Chokepoints and corridors are good places for traps
+        let trap_spots: Vec<Vec2d> = layout.chokepoints.iter()
+            .chain(layout.corridors.iter())
+            .cloned()
+            .collect();
+        for _ in 0..n_trap {
+            let p = if rng::get_rng().gen_bool(params.trap.chokepoint_ratio) {
+                choose_empty_tile_in(map, &trap_spots)
+            } else {
+                None
+            };
+            let p = if let Some(p) = p.or_else(|| choose_empty_tile(map)) { p } else { break; };
+            let kind = *rng::get_rng().choose(&trap_kinds).unwrap();
+            map.tile[p].special = SpecialTileKind::Trap { kind, hidden: true };
+        }
+    }
+
+    // Containers with some items.
+    // Locked containers are impassable, so they are not placed where they may block paths.
+    let container_spots: Vec<Vec2d> = map.tile.iter_idx()
+        .filter(|p| !layout.chokepoints.contains(p) && !layout.corridors.contains(p))
+        .collect();
+    let n_container = rng::gen_range(params.n_container[0], params.n_container[1] + 1);
+    for _ in 0..n_container {
+        let p = if let Some(p) = choose_empty_tile_in(map, &container_spots) { p } else { break; };
+        let lock_level = if rng::get_rng().gen_bool(params.lock_probability) { lock_level } else { 0 };
+        map.tile[p].special = SpecialTileKind::Container { lock_level };
+        for _ in 0..rng::gen_range(1, 4) {
+            map.locate_item(gen_dungeon_item_from_pool(floor, items), p, 1);
+        }
+    }
+}
+
+/// Add items and the boss of the theme for deepest floor of dungeon
 pub fn add_for_deepest_floor(gd: &mut GameData, mid: MapId) {
+    if let Some(boss) = get_theme(gd, mid.sid()).and_then(|theme| theme.boss.as_ref()) {
+        if let Some(idx) = checked_idx(boss) {
+            if let Some(p) = choose_empty_tile(gd.region.get_map(mid)) {
+                let chara = create_hostile_npc_chara(idx);
+                let cid = gd.add_chara_to_map(chara, mid);
+                if !gd.region.get_map_mut(mid).locate_chara(cid, p) {
+                    warn!("Failed to locate the boss \"{}\" at {:?}", boss, p);
+                }
+            } else {
+                warn!("Failed boss generating because empty tile not found");
+            }
+        }
+    }
+
     let map = gd.region.get_map_mut(mid);
 
     let p = if let Some(p) = ::game::map::choose_empty_tile(map) { p } else { return; };
 
     let idx: ItemIdx = gobj::id_to_idx("ancient-box");
     let item_obj: &ItemObject = gobj::get_obj(idx);
-    let item = Item {
-        idx: idx,
-        flags: item_obj.default_flags,
-        kind: item_obj.kind,
-        rank: ItemRank::default(),
-        attributes: vec![],
-    };
+    let item = Item::new(idx, item_obj.kind, item_obj.default_flags);
 
     let mut item_list = ItemList::new();
     item_list.append(item, 1);
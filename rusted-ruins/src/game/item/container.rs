@@ -0,0 +1,82 @@
+
+//! Functions for container items
+
+use common::gamedata::*;
+use super::gen::gen_item_by_level;
+use super::identify::hide_unknown_items;
+
+/// The max number of items generated in a container
+const MAX_CONTENTS: u32 = 4;
+
+/// Open the container. Its contents are generated at the first time.
+/// Returns the location of the contents list,
+/// or None if the item is not a container or cannot be opened there.
+pub fn open_container(gd: &mut GameData, il: ItemLocation) -> Option<ItemListLocation> {
+    let parent = match il.0 {
+        ItemListLocation::OnMap { mid, pos } => ContainerParent::OnMap { mid, pos },
+        ItemListLocation::Chara { cid } => ContainerParent::Chara { cid },
+        _ => { return None; }
+    };
+
+    let content_gen = {
+        let item = gd.get_item(il).0;
+        if item.kind != ItemKind::Container {
+            return None;
+        }
+        if item.contents().is_some() {
+            return Some(ItemListLocation::Container { parent, i: il.1 });
+        }
+        item.attributes.iter().filter_map(|attr| match attr {
+            ItemAttribute::ContentGen { level, seed } => Some((*level, *seed)),
+            _ => None,
+        }).next()
+    };
+
+    let mut contents = if let Some((level, seed)) = content_gen {
+        gen_contents(level, seed)
+    } else {
+        ItemList::new()
+    };
+    hide_unknown_items(&mut contents);
+
+    // Take one from the stack, because opened containers are never stacked
+    let mut item = gd.remove_item_and_get(il, 1);
+    item.attributes.retain(|attr| match attr {
+        ItemAttribute::ContentGen { .. } => false,
+        _ => true,
+    });
+    let id = gd.new_container_id();
+    item.attributes.push(ItemAttribute::Contents { id, items: contents });
+    let (_, i) = gd.append_item(il.0, item, 1);
+
+    Some(ItemListLocation::Container { parent, i })
+}
+
+/// Generate contents from the seed. The same seed always gives the same contents.
+fn gen_contents(level: u32, seed: u32) -> ItemList {
+    ::rng::reseed_with(seed as u64);
+
+    let mut contents = ItemList::new();
+    let n = ::rng::gen_range(1, MAX_CONTENTS + 1);
+    for _ in 0..n {
+        let item = gen_item_by_level(level, |item_obj| {
+            if item_obj.kind == ItemKind::Container { 0.0 } else { 1.0 }
+        });
+        contents.append(item, 1);
+    }
+
+    ::rng::reseed();
+    contents
+}
+
+/// Move items from the src to the container.
+/// Containers cannot be put into containers.
+/// Returns the container location, which is changed if the src is its parent list,
+/// or None if the item cannot be put.
+pub fn put_in(gd: &mut GameData, il: ItemLocation, container: ItemListLocation, n: u32)
+              -> Option<ItemListLocation> {
+    if gd.get_item(il).0.kind == ItemKind::Container {
+        return None;
+    }
+    Some(gd.put_in_item(il, container, n))
+}
@@ -0,0 +1,98 @@
+
+//! Functions for damage and repair of equipments
+
+use common::gamedata::*;
+use game::extrait::*;
+use rng::{self, Rng};
+use rules::RULES;
+
+/// The rate of dice and defence remaining for the item damage
+pub fn damage_rate(item: &Item) -> f32 {
+    let rate = 1.0 + item.rank.damage as f32 * RULES.durability.penalty_per_damage;
+    if rate < 0.0 { 0.0 } else { rate }
+}
+
+/// The weapon may be damaged when its wielder hits
+pub fn wear_weapon(gd: &mut GameData, cid: CharaId, esk: EquipSlotKind) {
+    if !rng::get_rng().gen_bool(RULES.durability.weapon_damage_probability) {
+        return;
+    }
+    damage_equipment(gd, cid, esk, 0);
+}
+
+/// One of armors may be damaged when its wearer is hit
+pub fn wear_armor(gd: &mut GameData, cid: CharaId) {
+    if !rng::get_rng().gen_bool(RULES.durability.armor_damage_probability) {
+        return;
+    }
+    let armors: Vec<(EquipSlotKind, u8)> = gd.get_equip_list(cid).item_iter()
+        .filter(|&(_, _, item)| match item.kind {
+            ItemKind::Armor(_) => true,
+            _ => false,
+        })
+        .map(|(esk, n, _)| (esk, n))
+        .collect();
+    if let Some(&(esk, n)) = rng::get_rng().choose(&armors) {
+        damage_equipment(gd, cid, esk, n as usize);
+    }
+}
+
+fn damage_equipment(gd: &mut GameData, cid: CharaId, esk: EquipSlotKind, n: usize) {
+    let item = if let Some(item) = gd.get_equip_list_mut(cid).item_mut(esk, n) { item } else { return; };
+    if item.rank.damage <= -RULES.durability.max_damage { return; }
+
+    item.rank.damage -= 1;
+    if cid == CharaId::Player {
+        game_log_i!("equipment-damaged"; item=item);
+    }
+}
+
+/// Repair fee at shops
+pub fn repair_fee(item: &Item) -> i64 {
+    let fee = item.price() as f32 * RULES.durability.repair_fee_rate * -item.rank.damage as f32;
+    ::std::cmp::max(fee as i64, 1)
+}
+
+/// Recover the item damage by the given value
+pub fn repair_item(gd: &mut GameData, il: ItemLocation, value: i8) {
+    let repair = |item: &mut Item| {
+        item.rank.damage = ::std::cmp::min(item.rank.damage.saturating_add(value), 0);
+        game_log_i!("item-repaired"; item=item);
+    };
+
+    if let ItemListLocation::Equip { cid } = il.0 {
+        repair(gd.get_equip_list_mut(cid).list_item_mut(il.1));
+        return;
+    }
+    // Rank is a part of the item order, so the item is inserted again
+    let list = gd.get_item_list_mut(il.0);
+    let (mut item, n) = list.items.remove(il.1 as usize);
+    repair(&mut item);
+    list.append(item, n);
+}
+
+/// Repair the player's item at the shop of the given shopkeeper
+pub fn repair_at_shop(gd: &mut GameData, il: ItemLocation, cid: CharaId) {
+    let (fee, damage) = {
+        let item = gd.get_item(il).0;
+        (repair_fee(item), item.rank.damage)
+    };
+    if !::game::shop::pay_fee(gd, cid, fee) { return; }
+    repair_item(gd, il, -damage);
+}
+
+/// Repair the item with a repair kit. The kit is consumed.
+pub fn repair_with_kit(gd: &mut GameData, kit: ItemLocation, il: ItemLocation) {
+    let value = ::common::gobj::get_obj(gd.get_item(kit).0.idx).eff;
+    let value = ::std::cmp::min(value, i8::max_value() as u16) as i8;
+    // Repairing re-sorts the list, so the kit is consumed first.
+    // If the kit stack is removed, the following items in the same list are shifted.
+    let kit_stack_removed = gd.get_item(kit).1 == 1;
+    gd.remove_item(kit, 1);
+    let il = if kit_stack_removed && kit.0 == il.0 && kit.1 < il.1 {
+        (il.0, il.1 - 1)
+    } else {
+        il
+    };
+    repair_item(gd, il, value);
+}
@@ -2,13 +2,20 @@
 use common::gobj;
 use common::gamedata::GameData;
 use common::gamedata::item::*;
+use text::ToText;
+use super::ItemEx;
 
 /// Used for creating filtered list and saving filtering state
 #[derive(Clone, Copy, Debug)]
 pub struct ItemFilter {
     pub all: bool,
     pub equip_slot_kind: Option<EquipSlotKind>,
+    pub kind_rough: Option<ItemKindRough>,
     pub flags: ItemFlags,
+    /// Items which have any of these flags are filtered
+    pub excluded_flags: ItemFlags,
+    pub unidentified: bool,
+    pub damaged: bool,
 }
 
 impl ItemFilter {
@@ -25,13 +32,23 @@ impl ItemFilter {
     /// Given item will be filtered (false) or not (true)
     pub fn judge(&self, item: &Item) -> bool {
         if self.all { return true; }
-        let o = gobj::get_obj(item.idx);
         
         if let Some(equip_slot_kind) = self.equip_slot_kind {
+            let o = gobj::get_obj(item.idx);
             if o.kind.equip_slot_kind() != Some(equip_slot_kind) { return false; }
         }
 
+        if let Some(kind_rough) = self.kind_rough {
+            if item.kind.rough() != kind_rough { return false; }
+        }
+
         if !item.flags.contains(self.flags) { return false; }
+
+        if item.flags.intersects(self.excluded_flags) { return false; }
+
+        if self.unidentified && super::identify::is_identified(item) { return false; }
+
+        if self.damaged && item.rank.damage >= 0 { return false; }
         
         true
     }
@@ -41,10 +58,30 @@ impl ItemFilter {
         self
     }
 
+    pub fn kind_rough(mut self, kind_rough: ItemKindRough) -> ItemFilter {
+        self.kind_rough = Some(kind_rough);
+        self
+    }
+
     pub fn flags(mut self, flags: ItemFlags) -> ItemFilter {
         self.flags = flags;
         self
     }
+
+    pub fn excluded_flags(mut self, flags: ItemFlags) -> ItemFilter {
+        self.excluded_flags = flags;
+        self
+    }
+
+    pub fn unidentified(mut self) -> ItemFilter {
+        self.unidentified = true;
+        self
+    }
+
+    pub fn damaged(mut self) -> ItemFilter {
+        self.damaged = true;
+        self
+    }
 }
 
 impl Default for ItemFilter {
@@ -52,11 +89,54 @@ impl Default for ItemFilter {
         ItemFilter {
             all: false,
             equip_slot_kind: None,
+            kind_rough: None,
             flags: ItemFlags::empty(),
+            excluded_flags: ItemFlags::empty(),
+            unidentified: false,
+            damaged: false,
+        }
+    }
+}
+
+/// Orders to display items
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ItemSortKind {
+    Kind, Weight, Price, Name,
+}
+
+impl ItemSortKind {
+    pub fn next(self) -> ItemSortKind {
+        match self {
+            ItemSortKind::Kind => ItemSortKind::Weight,
+            ItemSortKind::Weight => ItemSortKind::Price,
+            ItemSortKind::Price => ItemSortKind::Name,
+            ItemSortKind::Name => ItemSortKind::Kind,
         }
     }
 }
 
+/// Sort items for displaying. Heavier and more expensive items come first.
+/// Items in the same order are kept in the item list order.
+pub fn sort_items(items: &mut Vec<(ItemLocation, &Item, u32)>, sort_kind: ItemSortKind) {
+    match sort_kind {
+        ItemSortKind::Kind => (),
+        ItemSortKind::Weight => {
+            sort_by_key_descending(items, |item| item.w());
+        }
+        ItemSortKind::Price => {
+            sort_by_key_descending(items, |item| item.price());
+        }
+        ItemSortKind::Name => {
+            items.sort_by(|a, b| a.1.to_text().cmp(&b.1.to_text()));
+        }
+    }
+}
+
+/// Stable sort in descending order of the key
+fn sort_by_key_descending<K: Ord, F: Fn(&Item) -> K>(items: &mut Vec<(ItemLocation, &Item, u32)>, f: F) {
+    items.sort_by(|a, b| f(b.1).cmp(&f(a.1)));
+}
+
 #[derive(Clone)]
 pub struct FilteredItemList<'a> {
     item_list: &'a ItemList,
@@ -114,3 +194,68 @@ impl FilteredListHolder for GameData {
         FilteredItemList::new(item_list, list_location, filter)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::gamedata::CharaId;
+    use common::objholder::ItemIdx;
+
+    fn item(idx: u32, kind: ItemKind, flags: ItemFlags) -> Item {
+        Item::new(ItemIdx(idx), kind, flags)
+    }
+
+    #[test]
+    fn judge() {
+        let potion = item(0, ItemKind::Potion, ItemFlags::DRINKABLE);
+        let junk = item(1, ItemKind::Object, ItemFlags::JUNK);
+        let favorite_junk = item(2, ItemKind::Object, ItemFlags::JUNK | ItemFlags::FAVORITE);
+        let mut damaged = item(3, ItemKind::Object, ItemFlags::empty());
+        damaged.rank.damage = -1;
+
+        assert!(ItemFilter::all().judge(&favorite_junk));
+        assert!(ItemFilter::new().judge(&potion));
+
+        let filter = ItemFilter::new().kind_rough(ItemKindRough::Potion);
+        assert!(filter.judge(&potion));
+        assert!(!filter.judge(&junk));
+
+        let filter = ItemFilter::new().flags(ItemFlags::JUNK);
+        assert!(!filter.judge(&potion));
+        assert!(filter.judge(&junk));
+        assert!(filter.judge(&favorite_junk));
+
+        let filter = filter.excluded_flags(ItemFlags::FAVORITE);
+        assert!(filter.judge(&junk));
+        assert!(!filter.judge(&favorite_junk));
+
+        let filter = ItemFilter::new().damaged();
+        assert!(filter.judge(&damaged));
+        assert!(!filter.judge(&junk));
+    }
+
+    #[test]
+    fn sort_descending_and_stable() {
+        let items: Vec<Item> = [(0, 1), (1, 3), (2, 1), (3, 2), (4, 3)].iter()
+            .map(|&(idx, base)| {
+                let mut item = item(idx, ItemKind::Object, ItemFlags::empty());
+                item.rank.base = base;
+                item
+            })
+            .collect();
+        let ill = ItemListLocation::Chara { cid: CharaId::Player };
+        let mut list: Vec<(ItemLocation, &Item, u32)> = items.iter().enumerate()
+            .map(|(i, item)| ((ill, i as u32), item, 1))
+            .collect();
+
+        sort_by_key_descending(&mut list, |item| item.rank.base);
+        let order: Vec<u32> = list.iter().map(|&((_, i), _, _)| i).collect();
+        assert_eq!(order, vec![1, 4, 3, 0, 2]);
+
+        // Kind order keeps the item list order
+        list.sort_by_key(|&((_, i), _, _)| i);
+        sort_items(&mut list, ItemSortKind::Kind);
+        let order: Vec<u32> = list.iter().map(|&((_, i), _, _)| i).collect();
+        assert_eq!(order, vec![0, 1, 2, 3, 4]);
+    }
+}
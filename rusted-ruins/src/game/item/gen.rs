@@ -2,30 +2,93 @@
 use common::gamedata::*;
 use common::gobj;
 use common::objholder::ItemIdx;
+use rng::{self, Rng};
+use rules::RULES;
+use rules::affix::AffixParams;
 
 /// Generate new item on dungeon floor
 pub fn gen_dungeon_item(floor_level: u32) -> Item {
-    gen_item_by_level(floor_level, |_| 1.0)
+    let mut item = gen_item_by_level(floor_level, |_| 1.0);
+    add_affixes(&mut item, floor_level);
+    item
+}
+
+/// Generate new item on dungeon floor from the item pool.
+/// If the pool is empty or has no available items, items are chosen from all items.
+pub fn gen_dungeon_item_from_pool(floor_level: u32, pool: &[String]) -> Item {
+    if pool.is_empty() {
+        return gen_dungeon_item(floor_level);
+    }
+
+    let f = |item: &ItemObject| if pool.contains(&item.id) { 1.0 } else { 0.0 };
+    if let Some(idx) = choose_item_by_floor_level(floor_level, f) {
+        let mut item = gen_item_from_idx(idx, floor_level);
+        add_affixes(&mut item, floor_level);
+        item
+    } else {
+        gen_dungeon_item(floor_level)
+    }
+}
+
+/// Add a prefix and a suffix at random.
+/// Affixes are chosen from ones available for the item kind and the floor level.
+fn add_affixes(item: &mut Item, floor_level: u32) {
+    let rules = &RULES.affix;
+    let kind = item.kind.rough();
+
+    for &(probability, affixes) in &[(rules.prefix_probability, &rules.prefixes),
+                                     (rules.suffix_probability, &rules.suffixes)] {
+        let candidates: Vec<&AffixParams> = affixes.iter()
+            .filter(|affix| affix.level <= floor_level && affix.weight > 0.0 && affix.targets.contains(&kind))
+            .collect();
+        if candidates.is_empty() || !rng::get_rng().gen_bool(probability) {
+            continue;
+        }
+
+        let sum: f64 = candidates.iter().map(|affix| affix.weight).sum();
+        let mut r = rng::gen_range(0.0, sum);
+        for affix in &candidates {
+            if r < affix.weight {
+                item.attributes.push(ItemAttribute::Affix(affix.id.clone()));
+                break;
+            }
+            r -= affix.weight;
+        }
+    }
 }
 
 /// Generate new item by level.
 /// f is weight adjustment function.
 pub fn gen_item_by_level<F: FnMut(&ItemObject) -> f64>(level: u32, f: F) -> Item {
-    let idx = choose_item_by_floor_level(level, f);
+    let idx = choose_item_by_floor_level(level, f).expect("No item is available");
+    gen_item_from_idx(idx, level)
+}
 
+/// Generate new item from the index
+pub fn gen_item_from_idx(idx: ItemIdx, level: u32) -> Item {
     let item_obj = gobj::get_obj(idx);
     Item {
         idx: idx,
         flags: item_obj.default_flags,
         kind: item_obj.kind,
         rank: ItemRank::default(),
-        attributes: vec![],
+        attributes: default_attributes(item_obj.kind, level),
+    }
+}
+
+/// Containers have the seed to generate their contents when opened
+fn default_attributes(kind: ItemKind, level: u32) -> Vec<ItemAttribute> {
+    if kind == ItemKind::Container {
+        vec![ItemAttribute::ContentGen { level, seed: rng::next_u32() }]
+    } else {
+        vec![]
     }
 }
 
 /// Choose item by floor level.
 /// f is weight adjustment function.
-fn choose_item_by_floor_level<F: FnMut(&ItemObject) -> f64>(floor_level: u32, mut f: F) -> ItemIdx {
+/// Returns None if all weights are zero.
+pub fn choose_item_by_floor_level<F: FnMut(&ItemObject) -> f64>(floor_level: u32, mut f: F) -> Option<ItemIdx> {
     let items = &gobj::get_objholder().item;
 
     // Sum up gen_weight * weight_dist * dungeon_adjustment
@@ -40,19 +103,21 @@ fn choose_item_by_floor_level<F: FnMut(&ItemObject) -> f64>(floor_level: u32, mu
         }
     }
 
-    assert!(sum > 0.0);
+    if sum <= 0.0 {
+        return None;
+    }
 
     // Choose one chara
-    let r = ::rng::gen_range(0.0, sum);
+    let r = rng::gen_range(0.0, sum);
     let mut sum = 0.0;
     for (i, item) in items.iter().enumerate() {
         sum += weight_dist.calc(item.gen_level) * item.gen_weight as f64 * f(item);
         if r < sum {
-            return ItemIdx(i as u32);
+            return Some(ItemIdx(i as u32));
         }
     }
 
-    ItemIdx(first_available_item_idx.unwrap() as u32)
+    Some(ItemIdx(first_available_item_idx.unwrap() as u32))
 }
 
 struct CalcLevelWeightDist {
@@ -86,7 +151,7 @@ pub fn from_item_gen(item_gen: &ItemGen) -> Option<Item> {
             flags: item_obj.default_flags,
             kind: item_obj.kind,
             rank: ItemRank::default(),
-            attributes: vec![],
+            attributes: default_attributes(item_obj.kind, item_obj.gen_level),
         })
     } else {
         None
@@ -0,0 +1,162 @@
+
+//! Functions for item identification
+
+use std::cell::RefCell;
+use common::gamedata::*;
+use common::gobj;
+use common::objholder::ItemIdx;
+use game::extrait::*;
+use rng::{self, Rng};
+use rules::RULES;
+
+thread_local!(static IDENTIFY_TABLE: RefCell<IdentifyTable> = RefCell::new(IdentifyTable::default()));
+
+/// Weapons and armors are identified one by one.
+/// Potions are identified for each kind by the identify table.
+fn identified_one_by_one(kind: ItemKind) -> bool {
+    match kind {
+        ItemKind::Weapon(_) | ItemKind::Armor(_) => true,
+        _ => false,
+    }
+}
+
+/// Share the player's knowledge of the current game to show item names.
+/// Must be called when a game is started and the table is changed.
+pub fn share_table(table: &IdentifyTable) {
+    IDENTIFY_TABLE.with(|t| *t.borrow_mut() = table.clone());
+}
+
+/// The player knows the item or not.
+/// Potions are decided by the known kinds when shown, so they are not updated when learned.
+pub fn is_identified(item: &Item) -> bool {
+    if item.kind == ItemKind::Potion {
+        appearance(item).is_none()
+    } else {
+        item.is_identified()
+    }
+}
+
+/// Appearance id of the potion whose kind is unknown
+pub fn appearance(item: &Item) -> Option<String> {
+    if item.kind != ItemKind::Potion {
+        return None;
+    }
+    IDENTIFY_TABLE.with(|t| {
+        let t = t.borrow();
+        if t.is_known(item.idx) { None } else { t.appearance(item.idx).map(|a| a.to_owned()) }
+    })
+}
+
+/// Give shuffled appearances to all potion kinds at the start of a new game
+pub fn init_appearances(table: &mut IdentifyTable) {
+    let mut appearances = RULES.identify.potion_appearances.clone();
+    rng::get_rng().shuffle(&mut appearances);
+
+    let potions = gobj::get_objholder().item.iter().enumerate()
+        .filter(|&(_, item_obj)| item_obj.kind == ItemKind::Potion);
+    for ((i, _), appearance) in potions.zip(appearances.into_iter()) {
+        table.set_appearance(ItemIdx(i as u32), appearance);
+    }
+}
+
+/// Make the new weapon or armor unidentified
+pub fn hide_unknown_item(item: &mut Item) {
+    if !identified_one_by_one(item.kind) || !item.is_identified() {
+        return;
+    }
+    item.attributes.push(ItemAttribute::Unidentified);
+}
+
+/// Make new weapons and armors in the list unidentified
+pub fn hide_unknown_items(list: &mut ItemList) {
+    update_items(list, |item| hide_unknown_item(item));
+}
+
+/// Make new weapons and armors on the map unidentified
+pub fn hide_unknown_items_on_map(gd: &mut GameData, mid: MapId) {
+    let map = gd.region.get_map_mut(mid);
+    for p in map.tile.iter_idx() {
+        if let Some(ref mut list) = map.tile[p].item_list {
+            hide_unknown_items(list);
+        }
+    }
+}
+
+/// Modify items in the list, and keep the list sorted
+fn update_items<F: FnMut(&mut Item)>(list: &mut ItemList, mut f: F) {
+    let items = ::std::mem::replace(&mut list.items, Vec::new());
+    for (mut item, n) in items.into_iter() {
+        f(&mut item);
+        list.append(item, n);
+    }
+}
+
+/// Identify the item. Returns the new location of the item.
+pub fn identify_item(gd: &mut GameData, il: ItemLocation) -> ItemLocation {
+    let il = identify_without_log(gd, il);
+    game_log_i!("item-identified"; item=gd.get_item(il).0);
+    il
+}
+
+fn identify_without_log(gd: &mut GameData, il: ItemLocation) -> ItemLocation {
+    let list = gd.get_item_list_mut(il.0);
+    let (mut item, n) = list.items.remove(il.1 as usize);
+    item.identify();
+    let i = list.append(item, n);
+    (il.0, i)
+}
+
+/// The player learns the item kind, and all items of this kind are identified
+pub fn learn_kind(gd: &mut GameData, idx: ItemIdx) {
+    if gobj::get_obj(idx).kind != ItemKind::Potion || gd.identify.is_known(idx) {
+        return;
+    }
+    gd.identify.set_known(idx);
+    share_table(&gd.identify);
+
+    let name = ::text::obj_txt(gobj::idx_to_id(idx));
+    game_log_i!("item-kind-identified"; item=name);
+}
+
+/// Called before the player obtains the item.
+/// Unknown items may be identified by the appraisal skill.
+pub fn on_obtain(gd: &mut GameData, il: ItemLocation) -> ItemLocation {
+    let (idx, kind, is_identified) = {
+        let item = gd.get_item(il).0;
+        (item.idx, item.kind, is_identified(item))
+    };
+    if is_identified {
+        return il;
+    }
+
+    let chance = gd.chara.get(CharaId::Player).skill_effect(skill_id::APPRAISAL);
+    if !rng::get_rng().gen_bool(chance as f64) {
+        return il;
+    }
+    gd.chara.get_mut(CharaId::Player).add_skill_use_exp(skill_id::APPRAISAL);
+    if kind == ItemKind::Potion {
+        learn_kind(gd, idx);
+        il
+    } else {
+        identify_item(gd, il)
+    }
+}
+
+/// Identification fee at shops
+pub fn identify_fee(item: &Item) -> i64 {
+    ::std::cmp::max((item.price() as f32 * RULES.identify.shop_fee_rate) as i64, 1)
+}
+
+/// Identify the player's item at the shop of the given shopkeeper
+pub fn identify_at_shop(gd: &mut GameData, il: ItemLocation, cid: CharaId) {
+    let (idx, kind, fee) = {
+        let item = gd.get_item(il).0;
+        (item.idx, item.kind, identify_fee(item))
+    };
+    if !::game::shop::pay_fee(gd, cid, fee) { return; }
+    if kind == ItemKind::Potion {
+        learn_kind(gd, idx);
+    } else {
+        identify_item(gd, il);
+    }
+}
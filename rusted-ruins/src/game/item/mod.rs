@@ -1,6 +1,9 @@
 
+pub mod container;
+pub mod durability;
 pub mod filter;
 pub mod gen;
+pub mod identify;
 
 use common::gamedata::*;
 use common::gobj;
@@ -13,6 +16,10 @@ pub trait ItemEx {
     /// Calculate item selling price
     fn selling_price(&self) -> i64;
     fn w(&self) -> u32;
+    /// Weapon dice decreased by the damage
+    fn dice(&self) -> (u16, u16);
+    /// Defence decreased by the damage
+    fn def(&self) -> ElementArray<u16>;
 }
 
 impl ItemEx for Item {
@@ -28,8 +35,26 @@ impl ItemEx for Item {
 
     fn w(&self) -> u32 {
         let item_obj = gobj::get_obj(self.idx);
+        let contents_w: u32 = self.contents()
+            .map_or(0, |list| list.iter().map(|&(ref item, n)| item.w() * n).sum());
 
-        item_obj.w
+        item_obj.w + contents_w
+    }
+
+    fn dice(&self) -> (u16, u16) {
+        let item_obj = gobj::get_obj(self.idx);
+        let dice_x = (item_obj.dice_x as f32 * durability::damage_rate(self)) as u16;
+        (item_obj.dice_n, ::std::cmp::max(dice_x, 1))
+    }
+
+    fn def(&self) -> ElementArray<u16> {
+        let item_obj = gobj::get_obj(self.idx);
+        let rate = durability::damage_rate(self);
+        let mut def = item_obj.def;
+        for e in &ELEMENTS {
+            def[*e] = (def[*e] as f32 * rate) as u16;
+        }
+        def
     }
 }
 
@@ -50,15 +75,48 @@ impl ItemListEx for ItemList {
 }
 
 
+/// Favorite items cannot be given up, put into containers, or used up by the player.
+/// Returns true and logs if the item is protected.
+pub fn is_protected(gd: &GameData, il: ItemLocation) -> bool {
+    let item = gd.get_item(il).0;
+    if item.flags.contains(ItemFlags::FAVORITE) {
+        game_log_i!("item-favorite-protected"; item=item);
+        true
+    } else {
+        false
+    }
+}
+
+/// Toggle the player's mark on the item stack.
+/// Favorite and junk marks are exclusive.
+/// Returns the new location of the stack, because marking changes the sort order.
+pub fn toggle_mark(gd: &mut GameData, il: ItemLocation, mark: ItemFlags) -> ItemLocation {
+    let n = gd.get_item(il).1;
+    let mut item = gd.remove_item_and_get(il, n);
+    if item.flags.contains(mark) {
+        item.flags.remove(mark);
+    } else {
+        item.flags.remove(ItemFlags::FAVORITE | ItemFlags::JUNK);
+        item.flags.insert(mark);
+    }
+    let i = gd.get_item_list_mut(il.0).append(item, n);
+    (il.0, i)
+}
+
 /// Change specified character's equipment by given item
 pub fn change_equipment(
     gd: &mut GameData, cid: CharaId, slot: (EquipSlotKind, u8), il: ItemLocation) {
     
-    let item = gd.remove_item_and_get(il, 1);
+    let mut item = gd.remove_item_and_get(il, 1);
     
     game_log_i!("item-equip"; chara=gd.chara.get(cid), item=item);
+    if !item.is_identified() {
+        item.identify();
+        game_log_i!("item-identified"; item=item);
+    }
     if let Some(removed_equipment) = gd.get_equip_list_mut(cid).equip(slot.0, slot.1 as usize, item) {
         gd.get_item_list_mut(il.0).append(removed_equipment, 1);
     }
+    ::game::chara::update_params_by_id(gd, cid);
 }
 
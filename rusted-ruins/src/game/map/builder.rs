@@ -1,9 +1,12 @@
 
 use array2d::*;
+use rng::{self, Rng};
+use common::basic::N_TILE_IMG_LAYER;
 use common::objholder::*;
 use common::gamedata::map::*;
 use common::gobj;
-use map_generator::{MapGenerator, GeneratedMap, TileKind};
+use map_generator::{MapGenerator, GeneratedMap, TileKind, create_noise};
+use rules::dungeon_gen::MapGenKind;
 
 #[derive(Default)]
 pub struct MapBuilder {
@@ -12,6 +15,11 @@ pub struct MapBuilder {
     is_deepest_floor: bool,
     tile: TileIdx,
     wall: WallIdx,
+    map_gen: MapGenKind,
+    /// Tiles blended onto the base tile, and their ratio
+    layers: Vec<(TileIdx, f32)>,
+    deco: Vec<DecoIdx>,
+    deco_density: f64,
 }
 
 impl MapBuilder {
@@ -22,9 +30,31 @@ impl MapBuilder {
         map_builder
     }
     
-    pub fn build(self) -> Map {
-        let generated_map = MapGenerator::new((self.w, self.h)).fractal().generate();
-        generated_map_to_map(generated_map, self.tile, self.wall, self.floor, self.is_deepest_floor)
+    /// Build a map, and returns the generated layout with rooms, corridors and so on.
+    pub fn build(self) -> (Map, GeneratedMap) {
+        let generator = MapGenerator::new((self.w, self.h));
+        let generator = match self.map_gen {
+            MapGenKind::Fractal => generator.fractal(),
+            MapGenKind::Bsp { room_size, corridor_width } => {
+                generator.bsp(room_size[0], room_size[1], corridor_width)
+            }
+            MapGenKind::Cave { fill_ratio, n_smoothing } => {
+                if !(0.0 <= fill_ratio && fill_ratio <= 1.0) {
+                    warn!("Cave fill_ratio {} is out of [0, 1] and will be clamped", fill_ratio);
+                }
+                generator.cave(fill_ratio, n_smoothing)
+            }
+        };
+        let generated_map = generator.generate();
+        let mut map = generated_map_to_map(
+            &generated_map, self.tile, self.wall, self.floor, self.is_deepest_floor);
+        for (i, &(tile, ratio)) in self.layers.iter().take(N_TILE_IMG_LAYER - 1).enumerate() {
+            blend_tile_layer(&mut map, tile, ratio, i + 1);
+        }
+        if !self.deco.is_empty() {
+            put_decos(&mut map, &self.deco, self.deco_density);
+        }
+        (map, generated_map)
     }
 
     pub fn floor(mut self, floor: u32) -> MapBuilder {
@@ -42,13 +72,30 @@ impl MapBuilder {
         self
     }
 
+    /// Add a tile layer blended by noise
+    pub fn tile_layer(mut self, tile: TileIdx, ratio: f32) -> MapBuilder {
+        self.layers.push((tile, ratio));
+        self
+    }
+
+    pub fn deco(mut self, deco: Vec<DecoIdx>, density: f64) -> MapBuilder {
+        self.deco = deco;
+        self.deco_density = density;
+        self
+    }
+
+    pub fn map_gen(mut self, map_gen: MapGenKind) -> MapBuilder {
+        self.map_gen = map_gen;
+        self
+    }
+
     pub fn deepest_floor(mut self, is_deepest_floor: bool) -> MapBuilder {
         self.is_deepest_floor = is_deepest_floor;
         self
     }
 }
 
-pub fn generated_map_to_map(gm: GeneratedMap, tile: TileIdx, wall: WallIdx,
+pub fn generated_map_to_map(gm: &GeneratedMap, tile: TileIdx, wall: WallIdx,
                             floor: u32, is_deepest_floor: bool) -> Map {
     
     let size = gm.size;
@@ -80,6 +127,9 @@ pub fn generated_map_to_map(gm: GeneratedMap, tile: TileIdx, wall: WallIdx,
                     piece_pattern: piece_pattern,
                 };
             },
+            TileKind::Door => {
+                map.tile[p].special = SpecialTileKind::Door { open: false, lock_level: 0 };
+            },
             _ => (),
         }
     }
@@ -100,3 +150,44 @@ pub fn generated_map_to_map(gm: GeneratedMap, tile: TileIdx, wall: WallIdx,
     map
 }
 
+/// Put the tile on the given layer where noise values are high
+fn blend_tile_layer(map: &mut Map, tile: TileIdx, ratio: f32, layer: usize) {
+    if ratio <= 0.0 { return; }
+    let noise = create_noise((map.w as i32, map.h as i32));
+
+    // Calculate the threshold to cover tiles by the ratio
+    let mut v: Vec<f32> = noise.iter().cloned().collect();
+    v.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    let i = ((v.len() as f32 * (1.0 - ratio)) as usize).min(v.len() - 1);
+    let threshold = v[i];
+
+    let is_covered = |p: Vec2d| {
+        if let Some(value) = noise.get(p) { *value >= threshold } else { true }
+    };
+    let n_pattern = gobj::get_obj(tile).img.n_pattern;
+
+    for p in map.tile.iter_idx() {
+        if !is_covered(p) { continue; }
+
+        let mut piece_pattern_flags = PiecePatternFlags::new();
+        for dir in &Direction::EIGHT_DIRS {
+            piece_pattern_flags.set(*dir, is_covered(p + dir.as_vec()));
+        }
+        let piece_pattern = piece_pattern_flags.to_piece_pattern(n_pattern);
+        if piece_pattern.is_empty() { continue; }
+
+        map.tile[p].tile[layer] = TileIdxPP { idx: tile, piece_pattern };
+    }
+}
+
+/// Put decorations on floor tiles at random
+fn put_decos(map: &mut Map, deco: &[DecoIdx], density: f64) {
+    for p in map.tile.iter_idx() {
+        let tile = &mut map.tile[p];
+        if !tile.wall.is_empty() || !tile.special.is_none() { continue; }
+
+        if rng::get_rng().gen_bool(density) {
+            tile.deco = Some(*rng::get_rng().choose(deco).unwrap());
+        }
+    }
+}
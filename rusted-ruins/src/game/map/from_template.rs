@@ -1,8 +1,11 @@
 
+use array2d::Vec2d;
 use common::maptemplate::*;
 use common::gamedata::*;
 use common::gobj;
+use common::objholder::CharaTemplateIdx;
 use game::item::gen::from_item_gen;
+use game::chara::creation::create_hostile_npc_chara;
 
 pub fn from_template(t: &MapTemplateObject) -> Map {
     let mut map = create_terrain(t);
@@ -75,3 +78,26 @@ fn gen_items(map: &mut Map, t: &MapTemplateObject) {
     }
 }
 
+/// Generate characters listed in the template.
+/// offset is the position of the template on the map.
+pub fn gen_charas(gd: &mut GameData, mid: MapId, t: &MapTemplateObject, offset: Vec2d) {
+    for &(pos, ref id) in &t.charas {
+        let idx: CharaTemplateIdx = if let Some(idx) = gobj::id_to_idx_checked(id) {
+            idx
+        } else {
+            warn!("Unknown chara template \"{}\" in map template \"{}\"", id, t.id);
+            continue;
+        };
+        let p = offset + pos;
+        {
+            let map = gd.region.get_map(mid);
+            if !map.is_inside(p) || map.tile[p].chara.is_some() {
+                warn!("Cannot locate chara \"{}\" at {:?} in map template \"{}\"", id, pos, t.id);
+                continue;
+            }
+        }
+        let chara = create_hostile_npc_chara(idx);
+        let cid = gd.add_chara_to_map(chara, mid);
+        gd.region.get_map_mut(mid).locate_chara(cid, p);
+    }
+}
@@ -1,6 +1,7 @@
 
 pub mod builder;
 pub mod from_template;
+pub mod prefab;
 pub mod search;
 
 use array2d::*;
@@ -11,7 +12,7 @@ use common::obj::TileKind;
 use text::ToText;
 use super::Game;
 use super::chara::creation::create_npc_chara;
-use super::item::gen::gen_dungeon_item;
+use super::item::gen::gen_dungeon_item_from_pool;
 use rules::RULES;
 
 pub trait MapEx {
@@ -25,6 +26,10 @@ impl MapEx for Map {
         if !self.is_inside(pos) {
             return false;
         }
+
+        if !self.tile[pos].special.is_passable() {
+            return false;
+        }
         
         if self.tile[pos].wall.is_empty() {
             let tile = gobj::get_obj(self.tile[pos].tile.main_tile());
@@ -49,16 +54,18 @@ impl MapEx for Map {
 
 /// Switch current map to the specified map
 pub fn switch_map(game: &mut Game, mid: MapId) {
+    let prev_music = map_music(&game.gd, game.gd.get_current_mapid());
     {
         let gd = &mut game.gd;
         
         trace!("Switch map to {:?}", mid);
+        let prev_mid = gd.get_current_mapid();
+        super::site::lifecycle::before_switch_map(gd, prev_mid, mid);
         // If next_mid floor doesn't exist, create new floor
         if !mid.is_region_map() && gd.region.get_map_checked(mid).is_none() {
             info!("{:?} is not exist, so try to create new floor", mid);
             super::dungeon_gen::extend_site_floor(gd, mid.sid());
         }
-        let prev_mid = gd.get_current_mapid();
         gd.set_current_mapid(mid);
 
         let new_player_pos = if mid.is_region_map() && !prev_mid.is_region_map()
@@ -71,14 +78,34 @@ pub fn switch_map(game: &mut Game, mid: MapId) {
             };
     
         gd.get_current_map_mut().locate_chara(CharaId::Player, new_player_pos);
+        super::site::lifecycle::after_switch_map(gd, prev_mid, mid);
     }
     ::audio::play_sound("floor-change");
+    // Music is not restarted while moving between floors of the same dungeon
+    if let Some(music) = map_music(&game.gd, mid) {
+        if prev_music != Some(music) {
+            ::audio::play_music(music);
+        }
+    }
     super::view::update_view_map(game);
 }
 
+/// Music played on the map. Dungeons without theme music do not change music.
+fn map_music(gd: &GameData, mid: MapId) -> Option<&'static str> {
+    if mid.is_region_map() {
+        return Some(&RULES.params.region_music);
+    }
+    match gd.region.get_site(mid.sid()).content.kind() {
+        SiteKind::Town => Some(&RULES.town.music),
+        _ => super::dungeon_gen::get_theme(gd, mid.sid())
+            .and_then(|theme| theme.music.as_ref())
+            .map(|music| music.as_str()),
+    }
+}
+
 pub fn gen_npcs(gd: &mut GameData, mid: MapId, n: u32, floor_level: u32) {
     let dungeon_kind = match gd.region.get_site(mid.sid()).content {
-        SiteContent::AutoGenDungeon { dungeon_kind } => dungeon_kind,
+        SiteContent::AutoGenDungeon { dungeon_kind, .. } => dungeon_kind,
         _ => DungeonKind::Ruin,
     };
 
@@ -96,22 +123,31 @@ pub fn gen_npcs(gd: &mut GameData, mid: MapId, n: u32, floor_level: u32) {
     }
 }
 
+/// Empty tile don't has wall, chara, and isn't special tile.
+pub fn is_tile_empty(tile: &TileInfo) -> bool {
+    if tile.wall.is_empty() && tile.chara.is_none() && tile.special.is_none() {
+        let tile_idx = tile.tile.main_tile();
+        let tile_obj = gobj::get_obj(tile_idx);
+        tile_obj.kind == TileKind::Ground
+    } else {
+        false
+    }
+}
+
+/// Find the nearest empty tile from the given position
+pub fn nearest_empty_tile(map: &Map, pos: Vec2d) -> Option<Vec2d> {
+    let r = (map.w + map.h) as i32;
+    MDistRangeIter::new(pos, r)
+        .filter(|&(_, p)| map.is_inside(p) && is_tile_empty(&map.tile[p]))
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, p)| p)
+}
+
 /// Choose one empty tile in random
 pub fn choose_empty_tile(map: &Map) -> Option<Vec2d> {
     use rng::gen_range;
     const MAX_TRY: usize = 10;
 
-    // Function to determine the tile is empty or not
-    let is_tile_empty = |tile: &TileInfo| {
-        if tile.wall.is_empty() && tile.chara.is_none() && tile.special.is_none() {
-            let tile_idx = tile.tile.main_tile();
-            let tile_obj = gobj::get_obj(tile_idx);
-            tile_obj.kind == TileKind::Ground
-        } else {
-            false
-        }
-    };
-    
     for _ in 0..MAX_TRY {
         let p = Vec2d(gen_range(0, map.w) as i32, gen_range(0, map.h) as i32);
         let tile = &map.tile[p];
@@ -146,7 +182,7 @@ pub fn gen_items(gd: &mut GameData, mid: MapId) {
     let item_gen_probability = {
         let site = gd.region.get_site(mid.sid());
         match site.content {
-            SiteContent::AutoGenDungeon { dungeon_kind } => {
+            SiteContent::AutoGenDungeon { dungeon_kind, .. } => {
                 RULES.dungeon_gen[&dungeon_kind].item_gen_probability
             }
             _ => { return; } // No item generation
@@ -158,6 +194,7 @@ pub fn gen_items(gd: &mut GameData, mid: MapId) {
         warn!("invalid value {} for item_gen_probablility", item_gen_probability);
         return;
     };
+    let items = super::dungeon_gen::get_theme(gd, mid.sid()).map_or(&[][..], |theme| &theme.items);
     let map = gd.region.get_map_mut(mid);
 
     for p in map.tile.iter_idx() {
@@ -167,7 +204,7 @@ pub fn gen_items(gd: &mut GameData, mid: MapId) {
         }
 
         if get_rng().gen_bool(item_gen_probability) {
-            map.locate_item(gen_dungeon_item(mid.floor()), p, 1);
+            map.locate_item(gen_dungeon_item_from_pool(mid.floor(), items), p, 1);
         }
     }
     
@@ -186,9 +223,12 @@ pub fn update_observed_map(game: &mut Game) {
         observed_tile.tile = Some(tile.tile);
         observed_tile.wall = tile.wall;
         observed_tile.deco = tile.deco;
-        observed_tile.special = tile.special;
+        observed_tile.special = tile.special.observed();
         observed_tile.n_item = 0;
 
+        // Items in a locked container cannot be seen
+        if !tile.special.is_passable() { continue; }
+
         if let Some(ref item_list) = tile.item_list {
             for (i, &(ref item, _)) in item_list.iter().take(MAX_ITEM_FOR_DRAW).enumerate() {
                 observed_tile.items[i] = item.idx;
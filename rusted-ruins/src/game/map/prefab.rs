@@ -0,0 +1,119 @@
+
+//! Stitch small map templates (vaults, shrines, boss rooms) into generated maps
+
+use array2d::*;
+use rng::{self, Rng};
+use common::maptemplate::*;
+use common::gamedata::*;
+use common::gobj;
+use game::item::gen::from_item_gen;
+use map_generator::GeneratedMap;
+use super::search::{is_passable, reachable_tiles};
+
+/// The number of trials to locate a prefab at random position
+const MAX_TRY: usize = 20;
+
+/// Stitch the template into the map.
+/// The position is chosen from rooms large enough, or at random.
+/// Returns the top left position if succeeded.
+pub fn stitch_prefab(map: &mut Map, layout: &GeneratedMap, t: &MapTemplateObject) -> Option<Vec2d> {
+    let size = Vec2d(t.w as i32, t.h as i32);
+
+    let mut candidates: Vec<Vec2d> = layout.rooms.iter()
+        .filter(|room| room.w() >= size.0 && room.h() >= size.1)
+        .map(|room| Vec2d(
+            room.top_left.0 + rng::gen_range(0, room.w() - size.0 + 1),
+            room.top_left.1 + rng::gen_range(0, room.h() - size.1 + 1)))
+        .collect();
+    rng::get_rng().shuffle(&mut candidates);
+
+    // Random positions keeping edges of the map
+    if map.w as i32 > size.0 + 2 && map.h as i32 > size.1 + 2 {
+        for _ in 0..MAX_TRY {
+            candidates.push(Vec2d(
+                rng::gen_range(1, map.w as i32 - size.0 - 1),
+                rng::gen_range(1, map.h as i32 - size.1 - 1)));
+        }
+    }
+
+    let reachable = reachable_tiles(map, map.entrance);
+
+    for top_left in candidates.into_iter() {
+        if !is_free_area(map, top_left, size) { continue; }
+
+        let backup: Vec<TileInfo> = RectIter::new(top_left, top_left + size + (-1, -1))
+            .map(|p| map.tile[p].clone())
+            .collect();
+        write_template(map, t, top_left);
+
+        if keeps_connectivity(map, &reachable, top_left, size) {
+            return Some(top_left);
+        }
+
+        // Revert
+        for (p, tile) in RectIter::new(top_left, top_left + size + (-1, -1)).zip(backup.into_iter()) {
+            map.tile[p] = tile;
+        }
+    }
+
+    None
+}
+
+/// The area must be inside the map, and must not have special tiles and characters
+fn is_free_area(map: &Map, top_left: Vec2d, size: Vec2d) -> bool {
+    let right_bottom = top_left + size + (-1, -1);
+    if top_left.0 < 1 || top_left.1 < 1
+        || right_bottom.0 >= map.w as i32 - 1 || right_bottom.1 >= map.h as i32 - 1 {
+        return false;
+    }
+
+    RectIter::new(top_left, right_bottom).all(|p| {
+        let tile = &map.tile[p];
+        tile.special.is_none() && tile.chara.is_none() && p != map.entrance
+    })
+}
+
+/// Write terrains and items of the template
+fn write_template(map: &mut Map, t: &MapTemplateObject, top_left: Vec2d) {
+    for (pos, c) in t.tile.iter_with_idx() {
+        map.tile[top_left + pos].tile = TileLayers::conv_from(*c, &t.tile_table);
+        // Items on the area are replaced by the template's
+        map.tile[top_left + pos].item_list = None;
+    }
+
+    for (pos, c) in t.wall.iter_with_idx() {
+        map.tile[top_left + pos].wall = WallIdxPP::conv_from(*c, &t.wall_table);
+    }
+
+    for (pos, i) in t.deco.iter_with_idx() {
+        map.tile[top_left + pos].deco = if let Some(i) = *i {
+            Some(gobj::id_to_idx(&t.deco_table[i as usize]))
+        } else {
+            None
+        };
+    }
+
+    for &(pos, ref item_gen) in &t.items {
+        if let Some(item) = from_item_gen(item_gen) {
+            map.locate_item(item, top_left + pos, 1);
+        }
+    }
+}
+
+/// Tiles reachable from the start before stitching must remain reachable,
+/// and passable tiles in the prefab must be reachable.
+fn keeps_connectivity(map: &Map, reachable: &Array2d<bool>, top_left: Vec2d, size: Vec2d) -> bool {
+    let new_reachable = reachable_tiles(map, map.entrance);
+    let right_bottom = top_left + size + (-1, -1);
+    let in_prefab = |p: Vec2d| {
+        top_left.0 <= p.0 && p.0 <= right_bottom.0 && top_left.1 <= p.1 && p.1 <= right_bottom.1
+    };
+
+    map.tile.iter_idx().all(|p| {
+        if in_prefab(p) {
+            !is_passable(&map.tile[p]) || new_reachable[p]
+        } else {
+            !reachable[p] || new_reachable[p]
+        }
+    })
+}
@@ -1,6 +1,9 @@
 //! Functions to search objects in a map
 
+use array2d::*;
 use common::gamedata::*;
+use common::gobj;
+use common::obj::TileKind;
 use game::view::calc_visual_distance;
 
 /// Search the nearest chara's position that has given Relationship on the current map.
@@ -41,3 +44,30 @@ pub fn search_nearest_target(gd: &GameData, center_cid: CharaId, rel: Relationsh
 
     target_cid
 }
+
+/// Doors and other special tiles are regarded as passable here
+pub fn is_passable(tile: &TileInfo) -> bool {
+    tile.wall.is_empty() && gobj::get_obj(tile.tile.main_tile()).kind == TileKind::Ground
+}
+
+/// Tiles reachable from the start by walking over passable tiles
+pub fn reachable_tiles(map: &Map, start: Vec2d) -> Array2d<bool> {
+    let mut reachable = Array2d::new(map.w, map.h, false);
+    if !is_passable(&map.tile[start]) {
+        return reachable;
+    }
+    reachable[start] = true;
+    let mut stack = vec![start];
+
+    while let Some(p) = stack.pop() {
+        for dir in &Direction::EIGHT_DIRS {
+            let next = p + dir.as_vec();
+            if map.is_inside(next) && !reachable[next] && is_passable(&map.tile[next]) {
+                reachable[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    reachable
+}
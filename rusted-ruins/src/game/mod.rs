@@ -6,6 +6,7 @@ mod npc;
 mod action;
 mod command;
 mod region;
+mod region_gen;
 pub mod site;
 mod map;
 pub mod chara;
@@ -15,6 +16,8 @@ mod animation;
 mod anim_queue;
 pub mod newgame;
 mod combat;
+mod spell;
+mod special_tile;
 mod town;
 mod turnloop;
 pub mod view;
@@ -22,6 +25,8 @@ mod script;
 mod eval_expr;
 pub mod shop;
 mod dungeon_gen;
+pub mod danger;
+pub mod craft;
 
 use std::borrow::Cow;
 use array2d::Vec2d;
@@ -59,6 +64,7 @@ pub struct Game {
 
 impl Game {
     pub fn new(gd: GameData) -> Game {
+        self::item::identify::share_table(&gd.identify);
         let game = Game {
             gd: gd,
             state: GameState::PlayerTurn,
@@ -165,8 +171,12 @@ impl Game {
                 self.request_dialog_open(DialogOpenRequest::ShopBuy { cid });
                 AdvanceScriptResult::Continue
             }
-            ExecResult::ShopSell => {
-                self.request_dialog_open(DialogOpenRequest::ShopSell);
+            ExecResult::ShopSell(cid) => {
+                self.request_dialog_open(DialogOpenRequest::ShopSell { cid });
+                AdvanceScriptResult::Continue
+            }
+            ExecResult::ShopService(cid, kind) => {
+                self.request_dialog_open(DialogOpenRequest::ShopService { cid, kind });
                 AdvanceScriptResult::Continue
             }
         }
@@ -193,7 +203,8 @@ pub enum DialogOpenRequest {
     YesNo { callback: Box<FnMut(&mut DoPlayerAction, bool)>, msg: Cow<'static, str> },
     Talk { cid: CharaId, talk_text: TalkText },
     ShopBuy { cid: CharaId },
-    ShopSell,
+    ShopSell { cid: CharaId },
+    ShopService { cid: CharaId, kind: ServiceKind },
     GameOver,
 }
 
@@ -3,6 +3,7 @@ use common::gamedata;
 use common::gamedata::*;
 use common::gobj;
 use rules::RULES;
+use rng;
 use super::skill::SkillListEx;
 
 pub struct NewGameBuilder {
@@ -32,20 +33,29 @@ impl NewGameBuilder {
         {
             let mut gd = &mut self.gd;
 
+            rng::reseed();
+            gd.world_seed = rng::next_u32();
+            gd.save_id = format!("{:08x}-{:08x}", gd.world_seed, rng::next_u32());
+            super::item::identify::init_appearances(&mut gd.identify);
             super::region::add_region(&mut gd, &RULES.newgame.start_region);
 
             let mid = MapId::RegionMap { rid: RegionId::default() };
             gd.set_current_mapid(mid);
-            let start_pos = RULES.newgame.start_pos;
+            let start_pos = super::map::nearest_empty_tile(
+                gd.region.get_map(mid), RULES.newgame.start_pos).unwrap_or(RULES.newgame.start_pos);
 
-            super::region::gen_dungeon(&mut gd, mid.rid());
+            super::region::gen_initial_dungeon(&mut gd, mid.rid());
 
-            let chara_template_id = &RULES.newgame.chara_template_table[&self.chara_class.unwrap()];
+            let chara_class = self.chara_class.unwrap();
+            let chara_template_id = &RULES.newgame.chara_template_table[&chara_class];
             let mut chara = super::chara::creation::create_chara(gobj::id_to_idx(chara_template_id));
             chara.rel = gamedata::chara::Relationship::ALLY;
             chara.name = Some(self.player_name.unwrap());
+            chara.class = chara_class;
             set_initial_skills(&mut chara);
+            set_initial_spells(&mut chara);
             super::chara::update_params(&mut chara);
+            chara.mp = chara.params.max_mp;
 
             gd.player.set_money(RULES.newgame.start_money as i64);
             
@@ -71,7 +81,25 @@ impl NewGameBuilder {
 /// Set initial skills from rule
 fn set_initial_skills(chara: &mut Chara) {
     for skill in &RULES.newgame.common_initial_skills {
-        chara.skills.learn_new_skill(*skill);
+        chara.skills.learn_new_skill(skill.clone());
+    }
+    if let Some(class_skills) = RULES.newgame.class_initial_skills.get(&chara.class) {
+        for skill in class_skills {
+            chara.skills.learn_new_skill(skill.clone());
+        }
+    }
+}
+
+/// Set initial spells from rule
+fn set_initial_spells(chara: &mut Chara) {
+    if let Some(class_spells) = RULES.newgame.class_initial_spells.get(&chara.class) {
+        for id in class_spells {
+            if let Some(idx) = gobj::id_to_idx_checked(id) {
+                chara.spells.learn(idx);
+            } else {
+                warn!("Unknown spell id \"{}\"", id);
+            }
+        }
     }
 }
 
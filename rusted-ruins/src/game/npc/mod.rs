@@ -6,6 +6,7 @@ use array2d::*;
 use common::gamedata::*;
 use super::{Game, InfoGetter};
 use super::action;
+use super::extrait::*;
 use rng::*;
 
 pub fn process_npc_turn(game: &mut Game, cid: CharaId) {
@@ -43,6 +44,12 @@ fn random_walk(game: &mut Game, cid: CharaId) {
 /// Move npc to nearest enemy
 fn move_to_nearest_enemy(game: &mut Game, cid: CharaId) {
     if let Some(target) = map_search::search_nearest_enemy(&game.gd, cid) {
+        // Stealthy target may be unnoticed
+        if gen_range(0.0, 1.0) < game.gd.chara.get(target).skill_effect(skill_id::STEALTH) {
+            game.gd.chara.get_mut(target).add_skill_use_exp(skill_id::STEALTH);
+            random_walk(game, cid);
+            return;
+        }
         if let Some(pos) = game.gd.chara_pos(cid) {
             let dir = map_search::dir_to_chara(&game.gd, target, pos);
             action::try_move(game, cid, dir);
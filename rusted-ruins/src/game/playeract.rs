@@ -4,6 +4,9 @@ use super::action;
 use text::ToText;
 use common::gamedata::*;
 use game::{InfoGetter, DialogOpenRequest, AdvanceScriptResult};
+use game::extrait::*;
+use rules::RULES;
+use common::objholder::RecipeIdx;
 use array2d::*;
 
 /// Player actions are processed through this.
@@ -64,6 +67,7 @@ impl<'a> DoPlayerAction<'a> {
         }
         // Move to the next tile
         if action::try_move(self.0, CharaId::Player, dir) {
+            super::danger::report_site_danger(self.gd(), self.gd().player_pos());
             self.0.finish_player_turn();
         }
     }
@@ -135,6 +139,7 @@ impl<'a> DoPlayerAction<'a> {
                     }
                 }
                 super::map::switch_map(pa.0, next_mid);
+                super::danger::report_floor_danger(pa.gd());
             });
             self.0.request_dialog_open(DialogOpenRequest::YesNo {
                 callback: cb, msg: msg,
@@ -178,6 +183,7 @@ impl<'a> DoPlayerAction<'a> {
                     }
                 }
                 super::map::switch_map(pa.0, next_mid);
+                super::danger::report_floor_danger(pa.gd());
             });
             self.0.request_dialog_open(DialogOpenRequest::YesNo {
                 callback: cb, msg: msg_switch_map(next_mid),
@@ -199,47 +205,222 @@ impl<'a> DoPlayerAction<'a> {
         }
     }
 
-    /// Pick up an item on tile
+    /// Cast the nth spell of player's spell list
+    pub fn cast_spell(&mut self, n: usize) {
+        let spell_idx = if let Some(spell_idx) = self.gd().chara.get(CharaId::Player).spells.get(n) {
+            spell_idx
+        } else {
+            return;
+        };
+        if self.0.target_chara.is_none() {
+            self.0.target_chara = ::game::map::search::search_nearest_target(
+                self.gd(), CharaId::Player, Relationship::HOSTILE);
+        }
+
+        let target = self.0.target_chara;
+        if super::spell::cast_spell(&mut self.0, CharaId::Player, spell_idx, target) {
+            self.0.finish_player_turn();
+        }
+    }
+
+    /// Craft items by the recipe
+    pub fn craft(&mut self, recipe_idx: RecipeIdx) {
+        if super::craft::craft(self.gd_mut(), recipe_idx) {
+            self.0.finish_player_turn();
+        }
+    }
+
+    /// Close an open door next to player
+    pub fn close_door(&mut self) {
+        if super::special_tile::close_door(&mut self.0, CharaId::Player) {
+            self.0.finish_player_turn();
+        } else {
+            game_log_i!("no-door-to-close");
+        }
+    }
+
+    /// Pull the lever on player's tile
+    pub fn pull_lever(&mut self) {
+        let pos = self.gd().player_pos();
+        if super::special_tile::pull_lever(&mut self.0, CharaId::Player, pos) {
+            self.0.finish_player_turn();
+        }
+    }
+
+    /// Pick up an item on tile.
+    /// Returns false if the item is too heavy to carry.
     pub fn pick_up_item(&mut self, il: ItemLocation, n: u32) -> bool {
         let gd = self.gd_mut();
         let player_item_list_location = ItemListLocation::Chara { cid: CharaId::Player };
+        {
+            let player = gd.chara.get(CharaId::Player);
+            let item = gd.get_item(il).0;
+            let max_w = player.carry_capacity() as f32 * RULES.chara.max_carry_ratio;
+            if (player.carrying_w() + item.w() * n) as f32 > max_w {
+                game_log_i!("item-too-heavy"; chara=player, item=item);
+                return false;
+            }
+        }
         game_log_i!("item-pickup"; chara=gd.chara.get(CharaId::Player), item=gd.get_item(il).0);
+        let il = super::item::identify::on_obtain(gd, il);
         gd.move_item(il, player_item_list_location, n);
+        gd.chara.get_mut(CharaId::Player).update_burden();
         true
     }
 
     /// Drop items on tile
     pub fn drop_item(&mut self, il: ItemLocation, n: u32) -> bool {
         let gd = self.gd_mut();
+        if super::item::is_protected(gd, il) {
+            return false;
+        }
         let tile_list_location = ItemListLocation::OnMap {
             mid: gd.get_current_mapid(),
             pos: gd.player_pos(),
         };
         game_log_i!("item-drop"; chara=gd.chara.get(CharaId::Player), item=gd.get_item(il).0);
         gd.move_item(il, tile_list_location, n);
+        gd.chara.get_mut(CharaId::Player).update_burden();
         true
     }
 
+    /// Open a container, and returns the location of its contents
+    pub fn open_container(&mut self, il: ItemLocation) -> Option<ItemListLocation> {
+        let gd = self.gd_mut();
+        let ill = super::item::container::open_container(gd, il)?;
+        // The container may be moved in the list by opening
+        let container_il = match ill {
+            ItemListLocation::Container { parent, i } => (parent.into(), i),
+            _ => unreachable!(),
+        };
+        game_log_i!("container-open"; chara=gd.chara.get(CharaId::Player), item=gd.get_item(container_il).0);
+        Some(ill)
+    }
+
+    /// Take items out of the container to player's inventory.
+    /// Returns the container location which may be changed by this.
+    pub fn take_out_item(&mut self, il: ItemLocation, n: u32) -> ItemListLocation {
+        let gd = self.gd_mut();
+        let player_item_list_location = ItemListLocation::Chara { cid: CharaId::Player };
+        game_log_i!("item-take-out"; chara=gd.chara.get(CharaId::Player), item=gd.get_item(il).0);
+        let il = super::item::identify::on_obtain(gd, il);
+        gd.take_out_item(il, player_item_list_location, n)
+    }
+
+    /// Put items into the container.
+    /// Returns the container location which may be changed by this.
+    pub fn put_in_item(&mut self, il: ItemLocation, container: ItemListLocation, n: u32)
+                       -> ItemListLocation {
+        let gd = self.gd_mut();
+        if super::item::is_protected(gd, il) {
+            return container;
+        }
+        let item_text = gd.get_item(il).0.to_text().to_string();
+        if let Some(container) = super::item::container::put_in(gd, il, container, n) {
+            game_log_i!("item-put-in"; chara=gd.chara.get(CharaId::Player), item=item_text);
+            container
+        } else {
+            game_log_i!("cannot-put-container"; item=item_text);
+            container
+        }
+    }
+
+    /// Split items from the stack
+    pub fn split_item(&mut self, il: ItemLocation, n: u32) {
+        self.gd_mut().get_item_list_mut(il.0).split(il.1, n);
+    }
+
+    /// Mark the item stack as favorite or junk, or unmark it.
+    /// Returns the new location of the stack.
+    pub fn toggle_item_mark(&mut self, il: ItemLocation, mark: ItemFlags) -> ItemLocation {
+        super::item::toggle_mark(self.gd_mut(), il, mark)
+    }
+
     /// Drink one item
     pub fn drink_item(&mut self, il: ItemLocation) {
+        if super::item::is_protected(self.gd(), il) {
+            return;
+        }
+        let idx = self.gd().get_item(il).0.idx;
         super::action::drink_item(self.gd_mut(), il, CharaId::Player);
+        super::item::identify::learn_kind(self.gd_mut(), idx);
         self.0.finish_player_turn();
     }
 
     /// Eat one item
     pub fn eat_item(&mut self, il: ItemLocation) {
+        if super::item::is_protected(self.gd(), il) {
+            return;
+        }
         super::action::eat_item(self.gd_mut(), il, CharaId::Player);
         self.0.finish_player_turn();
     }
 
-    /// Buy item
-    pub fn buy_item(&mut self, il: ItemLocation) {
-        super::shop::buy_item(self.gd_mut(), il);
+    /// Buy items
+    pub fn buy_item(&mut self, il: ItemLocation, n: ItemMoveNum) {
+        let gd = self.gd_mut();
+        if super::shop::buy_item(gd, il, n) {
+            gd.chara.get_mut(CharaId::Player).update_burden();
+        }
+    }
+
+    /// Sell items to the shop of the given shopkeeper
+    pub fn sell_item(&mut self, il: ItemLocation, cid: CharaId, n: ItemMoveNum) {
+        let gd = self.gd_mut();
+        if super::shop::sell_item(gd, il, cid, n) {
+            gd.chara.get_mut(CharaId::Player).update_burden();
+        }
+    }
+
+    /// Sell all items marked as junk to the shop of the given shopkeeper
+    pub fn sell_junk(&mut self, cid: CharaId) {
+        let gd = self.gd_mut();
+        super::shop::sell_junk(gd, cid);
+        gd.chara.get_mut(CharaId::Player).update_burden();
+    }
+
+    /// Identify item at a shop
+    pub fn identify_item_at_shop(&mut self, il: ItemLocation, cid: CharaId) {
+        super::item::identify::identify_at_shop(self.gd_mut(), il, cid);
+    }
+
+    /// Repair equipment at a shop
+    pub fn repair_item_at_shop(&mut self, il: ItemLocation, cid: CharaId) {
+        super::item::durability::repair_at_shop(self.gd_mut(), il, cid);
+    }
+
+    /// Use a service which does not need to choose items at the shop of the given shopkeeper
+    pub fn use_shop_service(&mut self, cid: CharaId, kind: ServiceKind) {
+        match kind {
+            ServiceKind::Heal => super::shop::heal_at_shop(self.gd_mut(), cid),
+            ServiceKind::Inn => super::shop::stay_at_inn(self.gd_mut(), cid),
+            _ => (),
+        }
+    }
+
+    /// Deposit an item to the storage of the given shopkeeper
+    pub fn deposit_item(&mut self, il: ItemLocation, cid: CharaId) {
+        let gd = self.gd_mut();
+        if super::item::is_protected(gd, il) {
+            return;
+        }
+        game_log_i!("item-deposit"; chara=gd.chara.get(CharaId::Player), item=gd.get_item(il).0);
+        gd.move_item(il, ItemListLocation::Shop { cid }, 1);
+        gd.chara.get_mut(CharaId::Player).update_burden();
     }
 
-    /// Sell item
-    pub fn sell_item(&mut self, il: ItemLocation) {
-        super::shop::sell_item(self.gd_mut(), il);
+    /// Withdraw an item from the storage
+    pub fn withdraw_item(&mut self, il: ItemLocation) {
+        let gd = self.gd_mut();
+        game_log_i!("item-withdraw"; chara=gd.chara.get(CharaId::Player), item=gd.get_item(il).0);
+        gd.move_item(il, ItemListLocation::Chara { cid: CharaId::Player }, 1);
+        gd.chara.get_mut(CharaId::Player).update_burden();
+    }
+
+    /// Repair equipment with a repair kit
+    pub fn repair_item_with_kit(&mut self, kit: ItemLocation, il: ItemLocation) {
+        super::item::durability::repair_with_kit(self.gd_mut(), kit, il);
+        self.0.finish_player_turn();
     }
 
     /// Change specified character's equipment by given item
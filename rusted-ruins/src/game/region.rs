@@ -3,13 +3,27 @@ use common::basic::MAX_AUTO_GEN_DUNGEONS;
 use common::gamedata::*;
 use common::regiongen::*;
 use common::gobj;
-use super::map::choose_empty_tile;
+use common::obj::TileKind;
+use array2d::*;
+use super::map::{is_tile_empty, nearest_empty_tile};
+use super::map::search::reachable_tiles;
 use rng::{self, Rng};
+use rules::RULES;
+use rules::dungeon_gen::SiteTerrain;
 
 pub fn add_region(gd: &mut GameData, id: &str) {
     let rg: &RegionGenObject = gobj::get_by_id(id);
-    
-    let map = if let Some(map) = super::map::from_template::from_template_id(&rg.id) {
+
+    // Regions are generated from the world seed
+    rng::reseed_with(region_seed(gd.world_seed, id));
+
+    let generated_map = RULES.region_gen.get(id).and_then(|params| {
+        let towns: Vec<Vec2d> = rg.towns.iter().map(|&(_, pos)| pos).collect();
+        super::region_gen::gen_region_map(params, &towns)
+    });
+    let map = if let Some(map) = generated_map {
+        map
+    } else if let Some(map) = super::map::from_template::from_template_id(&rg.id) {
         map
     } else {
         error!("Map generation failed from \"{}\"", rg.id);
@@ -19,6 +33,28 @@ pub fn add_region(gd: &mut GameData, id: &str) {
     let region = Region::new(id, map);
     let rid = gd.region.add_region(region);
     add_sites_from_genobj(gd, rg, rid);
+
+    rng::reseed();
+}
+
+/// Calculate the seed for the region from its id
+fn region_seed(world_seed: u32, id: &str) -> u64 {
+    // FNV-1a hash
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in id.bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash ^ world_seed as u64
+}
+
+/// Generate dungeons placed at the start of the game.
+/// They are generated from the world seed like the region map.
+pub fn gen_initial_dungeon(gd: &mut GameData, rid: RegionId) {
+    let seed = region_seed(gd.world_seed, &format!("{}/dungeon", gd.region.get(rid).name));
+    rng::reseed_with(seed);
+    gen_dungeon(gd, rid);
+    rng::reseed();
 }
 
 /// Generate dungeons up to the max
@@ -39,9 +75,16 @@ pub fn gen_dungeon_max(gd: &mut GameData, rid: RegionId) {
 pub fn gen_dungeon(gd: &mut GameData, rid: RegionId) {
     if MAX_AUTO_GEN_DUNGEONS <= gd.region.get(rid).get_site_n(SiteKind::AutoGenDungeon) { return; }
     
+    let dungeon_kind = *rng::get_rng().choose(&[DungeonKind::Cave, DungeonKind::Ruin]).unwrap();
     let pos = {
         let region_map = gd.region.get_map(MapId::from(rid));
-        match choose_empty_tile(region_map) {
+        let site_terrain = RULES.dungeon_gen[&dungeon_kind].site_terrain;
+        let start = if let Some(pos) = region_map.chara_pos(CharaId::Player) {
+            pos
+        } else {
+            nearest_empty_tile(region_map, RULES.newgame.start_pos).unwrap_or(RULES.newgame.start_pos)
+        };
+        match choose_site_pos(region_map, site_terrain, start) {
             Some(pos) => pos,
             None => {
                 warn!("Dungeon generation failed: No empty tile");
@@ -49,7 +92,6 @@ pub fn gen_dungeon(gd: &mut GameData, rid: RegionId) {
             }
         }
     };
-    let dungeon_kind = *rng::get_rng().choose(&[DungeonKind::Cave, DungeonKind::Ruin]).unwrap();
     
     super::dungeon_gen::add_dungeon_site(gd, dungeon_kind, pos);
     let region_map = gd.region.get_map_mut(MapId::from(rid));
@@ -60,6 +102,39 @@ pub fn gen_dungeon(gd: &mut GameData, rid: RegionId) {
     region_map.tile[pos].special = SpecialTileKind::SiteSymbol { kind: site_symbol_kind };
 }
 
+/// Choose an empty tile reachable from the start which satisfies the terrain condition.
+/// If there is no such tile, choose from all reachable empty tiles.
+fn choose_site_pos(map: &Map, site_terrain: SiteTerrain, start: Vec2d) -> Option<Vec2d> {
+    let around = |p: Vec2d, f: fn(&TileInfo) -> bool| {
+        Direction::EIGHT_DIRS.iter().any(|dir| {
+            let q = p + dir.as_vec();
+            map.is_inside(q) && f(&map.tile[q])
+        })
+    };
+    let has_wall = |tile: &TileInfo| !tile.wall.is_empty();
+    let is_water = |tile: &TileInfo| gobj::get_obj(tile.tile.main_tile()).kind == TileKind::Water;
+
+    let reachable = reachable_tiles(map, start);
+    let empty_tiles: Vec<Vec2d> = map.tile.iter_idx()
+        .filter(|&p| p != start && reachable[p] && is_tile_empty(&map.tile[p]))
+        .collect();
+    let candidates: Vec<Vec2d> = empty_tiles.iter()
+        .cloned()
+        .filter(|&p| match site_terrain {
+            SiteTerrain::Any => true,
+            SiteTerrain::NearMountain => around(p, has_wall),
+            SiteTerrain::Plain => !around(p, has_wall) && !around(p, is_water),
+            SiteTerrain::Coast => around(p, is_water),
+        })
+        .collect();
+
+    if let Some(pos) = rng::get_rng().choose(&candidates) {
+        Some(*pos)
+    } else {
+        rng::get_rng().choose(&empty_tiles).cloned()
+    }
+}
+
 fn add_sites_from_genobj(gd: &mut GameData, rg: &RegionGenObject, rid: RegionId) {
     // Add towns
     for &(ref site_gen_id, pos) in &rg.towns {
@@ -0,0 +1,175 @@
+//! This module provides functions for procedural region map generation
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use array2d::*;
+use rng::{self, Rng};
+use common::gamedata::*;
+use common::objholder::*;
+use common::gobj;
+use map_generator::{create_noise, create_edge_biased_noise};
+use rules::region_gen::RegionGenParams;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Terrain {
+    Ground, Water, Mountain, Forest, Road,
+}
+
+impl Terrain {
+    /// The cost to build roads on this terrain
+    fn road_cost(self) -> u32 {
+        match self {
+            Terrain::Ground | Terrain::Road => 1,
+            Terrain::Forest => 2,
+            Terrain::Mountain => 6,
+            Terrain::Water => 10,
+        }
+    }
+}
+
+/// Generate a region map from the heightmap.
+/// Lands around towns are kept, and towns are connected by roads.
+/// Returns None if the parameters have unknown object ids.
+pub fn gen_region_map(params: &RegionGenParams, towns: &[Vec2d]) -> Option<Map> {
+    let ground: TileIdx = checked_idx(&params.ground)?;
+    let water: TileIdx = checked_idx(&params.water)?;
+    let road: TileIdx = checked_idx(&params.road)?;
+    let mountain: WallIdx = checked_idx(&params.mountain)?;
+    let forest: Vec<DecoIdx> = params.forest.iter().filter_map(|id| checked_idx(id)).collect();
+
+    let size = params.map_size;
+    if towns.iter().any(|p| p.0 < 0 || p.1 < 0 || p.0 >= size.0 || p.1 >= size.1) {
+        warn!("Towns are out of the region map size {:?}", size);
+        return None;
+    }
+
+    let mut terrain = create_terrain(params);
+    for &town in towns {
+        for (_, p) in MDistRangeIter::new(town, 2) {
+            if terrain.in_range(p) {
+                terrain[p] = Terrain::Ground;
+            }
+        }
+    }
+    build_roads(&mut terrain, towns);
+
+    let mut map = Map::new(size.0 as u32, size.1 as u32);
+    let n_pattern = gobj::get_obj(mountain).img.n_pattern;
+    for p in terrain.iter_idx() {
+        map.tile[p].tile = match terrain[p] {
+            Terrain::Water => water,
+            Terrain::Road => road,
+            _ => ground,
+        }.into();
+
+        match terrain[p] {
+            Terrain::Mountain => {
+                let mut piece_pattern_flags = PiecePatternFlags::new();
+                for dir in &Direction::EIGHT_DIRS {
+                    let is_mountain = terrain.get(p + dir.as_vec()).map_or(true, |t| *t == Terrain::Mountain);
+                    piece_pattern_flags.set(*dir, is_mountain);
+                }
+                map.tile[p].wall = WallIdxPP {
+                    idx: mountain,
+                    piece_pattern: piece_pattern_flags.to_piece_pattern(n_pattern),
+                };
+            }
+            Terrain::Forest => {
+                map.tile[p].deco = rng::get_rng().choose(&forest).cloned();
+            }
+            _ => (),
+        }
+    }
+
+    Some(map)
+}
+
+/// Create terrain from noises.
+/// The height noise is high at edges, so the heightmap is inverted to make coasts at edges.
+fn create_terrain(params: &RegionGenParams) -> Array2d<Terrain> {
+    let size = params.map_size;
+    let height = create_edge_biased_noise(size);
+    let water_level = quantile(&height, 1.0 - params.water_ratio);
+    let mountain_level = quantile(&height, params.mountain_ratio);
+    let forest_noise = create_noise(size);
+    let forest_level = quantile(&forest_noise, 1.0 - params.forest_ratio);
+
+    let mut terrain = Array2d::new(size.0 as u32, size.1 as u32, Terrain::Ground);
+    for p in terrain.iter_idx() {
+        terrain[p] = if params.water_ratio > 0.0 && height[p] >= water_level {
+            Terrain::Water
+        } else if params.mountain_ratio > 0.0 && height[p] <= mountain_level {
+            Terrain::Mountain
+        } else if params.forest_ratio > 0.0 && forest_noise[p] >= forest_level {
+            Terrain::Forest
+        } else {
+            Terrain::Ground
+        };
+    }
+    terrain
+}
+
+/// Returns the value which the given ratio of values are lower than
+fn quantile(a: &Array2d<f32>, ratio: f32) -> f32 {
+    let mut v: Vec<f32> = a.iter().cloned().collect();
+    v.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    let i = ((v.len() as f32 * ratio) as usize).min(v.len() - 1);
+    v[i]
+}
+
+/// Connect each town to the nearest one of previous towns
+fn build_roads(terrain: &mut Array2d<Terrain>, towns: &[Vec2d]) {
+    for i in 1..towns.len() {
+        let start = towns[i];
+        let goal = *towns[..i].iter().min_by_key(|p| p.mdistance(start)).unwrap();
+
+        for p in find_road_route(terrain, start, goal) {
+            if p != start && p != goal {
+                terrain[p] = Terrain::Road;
+            }
+        }
+    }
+}
+
+/// Find the cheapest route by Dijkstra's algorithm
+fn find_road_route(terrain: &Array2d<Terrain>, start: Vec2d, goal: Vec2d) -> Vec<Vec2d> {
+    let (w, h) = terrain.size();
+    let mut cost = Array2d::new(w, h, u32::max_value());
+    let mut prev: Array2d<Option<Vec2d>> = Array2d::new(w, h, None);
+    let mut heap = BinaryHeap::new();
+    cost[start] = 0;
+    heap.push(Reverse((0, start.0, start.1)));
+
+    while let Some(Reverse((c, x, y))) = heap.pop() {
+        let p = Vec2d(x, y);
+        if p == goal { break; }
+        if c > cost[p] { continue; }
+
+        for d in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let next = p + *d;
+            if !terrain.in_range(next) { continue; }
+            let next_cost = c + terrain[next].road_cost();
+            if next_cost < cost[next] {
+                cost[next] = next_cost;
+                prev[next] = Some(p);
+                heap.push(Reverse((next_cost, next.0, next.1)));
+            }
+        }
+    }
+
+    let mut route = vec![goal];
+    let mut p = goal;
+    while let Some(q) = prev[p] {
+        route.push(q);
+        p = q;
+    }
+    route
+}
+
+fn checked_idx<T: ObjectIndex>(id: &str) -> Option<T> {
+    let idx = gobj::id_to_idx_checked(id);
+    if idx.is_none() {
+        warn!("Unknown object id \"{}\" for region map generation", id);
+    }
+    idx
+}
@@ -18,7 +18,8 @@ pub struct ScriptEngine {
 pub enum ExecResult {
     Talk(CharaId, TalkText, bool),
     ShopBuy(CharaId),
-    ShopSell,
+    ShopSell(CharaId),
+    ShopService(CharaId, ServiceKind),
     Quit,
 }
 
@@ -141,7 +142,10 @@ impl ScriptEngine {
                     break ExecResult::ShopBuy(ur!(self.cid, "cid is needed"));
                 }
                 Instruction::ShopSell => {
-                    break ExecResult::ShopSell;
+                    break ExecResult::ShopSell(ur!(self.cid, "cid is needed"));
+                }
+                Instruction::ShopService(kind) => {
+                    break ExecResult::ShopService(ur!(self.cid, "cid is needed"), *kind);
                 }
                 Instruction::GetDungeonLocation => {
                     let mid = gd.get_current_mapid();
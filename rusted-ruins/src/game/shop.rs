@@ -2,37 +2,239 @@
 use common::gamedata::*;
 use rules::RULES;
 use game::extrait::*;
-use game::item::gen::gen_item_by_level;
+use game::item::gen::{choose_item_by_floor_level, gen_item_from_idx};
+use game::item::filter::*;
+use rng::Rng;
 
-pub fn buy_item(gd: &mut GameData, il: ItemLocation) {
-    let price = gd.get_item(il).0.price();
-    if gd.player.has_money(price) {
-        gd.player.sub_money(price);
-        gd.move_item(il, ItemListLocation::Chara { cid: CharaId::Player }, 1);
-    } else {
+/// Buy items from the shop. Returns false if the player does not have enough money.
+pub fn buy_item(gd: &mut GameData, il: ItemLocation, n: ItemMoveNum) -> bool {
+    let cid = shop_cid(il);
+    let n = n.to_u32(gd.get_item(il).1);
+    let price = buy_total(gd, il, n);
+    if !gd.player.has_money(price) {
         game_log_i!("shop-lack-of-money"; chara=gd.chara.get(CharaId::Player));
+        return false;
     }
+    gd.player.sub_money(price);
+    let mut item = gd.remove_item_and_get(il, n);
+    item.attributes.retain(|attr| match attr {
+        ItemAttribute::Buyback { .. } => false,
+        _ => true,
+    });
+    gd.get_item_list_mut(ItemListLocation::Chara { cid: CharaId::Player }).append(item, n);
+    gd.chara.get_mut(CharaId::Player).add_skill_use_exp(skill_id::TRADING);
+    gd.get_shop_mut(cid).money += price;
+    add_prosperity(gd, cid, price);
+    true
 }
 
-pub fn sell_item(gd: &mut GameData, il: ItemLocation) {
-    let price = gd.get_item(il).0.selling_price();
+/// Sell items to the shop of the given shopkeeper.
+/// Sold items are identified and added to the shop stock for buyback.
+/// Returns false if the item is a favorite or the shop does not have enough money.
+pub fn sell_item(gd: &mut GameData, il: ItemLocation, cid: CharaId, n: ItemMoveNum) -> bool {
+    if super::item::is_protected(gd, il) {
+        return false;
+    }
+    let n = n.to_u32(gd.get_item(il).1);
+    let unit_price = sell_price(gd.get_item(il).0, gd.chara.get(CharaId::Player), gd.get_shop(cid));
+    let price = unit_price * n as i64;
+    if gd.get_shop(cid).money < price {
+        game_log_i!("shop-lack-of-shop-money"; chara=gd.chara.get(cid));
+        return false;
+    }
     gd.player.add_money(price);
-    gd.remove_item(il, 1);
+    let mut item = gd.remove_item_and_get(il, n);
+    item.identify();
+    // The player's marks are not kept by the shop
+    item.flags.remove(ItemFlags::FAVORITE | ItemFlags::JUNK);
+    item.attributes.push(ItemAttribute::Buyback { price: unit_price });
+    {
+        let shop = gd.get_shop_mut(cid);
+        shop.money -= price;
+        shop.add_sold(item.idx, n);
+        shop.items.append(item, n);
+    }
+    gd.chara.get_mut(CharaId::Player).add_skill_use_exp(skill_id::TRADING);
+    add_prosperity(gd, cid, price);
+    true
+}
+
+/// Sell all items marked as junk in the player's inventory except favorites
+pub fn sell_junk(gd: &mut GameData, cid: CharaId) {
+    let ill = ItemListLocation::Chara { cid: CharaId::Player };
+    let mut junk_list: Vec<ItemLocation> = gd.get_filtered_item_list(ill, junk_filter())
+        .map(|(il, _, _)| il)
+        .collect();
+    // Sell from the end of the list not to change the locations of remaining items
+    junk_list.reverse();
+    for il in junk_list.into_iter() {
+        if !sell_item(gd, il, cid, ItemMoveNum::All) {
+            return;
+        }
+    }
+}
+
+/// The number of items and the total price of the player's junk
+pub fn junk_total(gd: &GameData, cid: CharaId) -> (u32, i64) {
+    let ill = ItemListLocation::Chara { cid: CharaId::Player };
+    let player = gd.chara.get(CharaId::Player);
+    let shop = gd.get_shop(cid);
+    gd.get_filtered_item_list(ill, junk_filter())
+        .fold((0, 0), |(sum_n, sum_price), (_, item, n)| {
+            (sum_n + n, sum_price + sell_price(item, player, shop) * n as i64)
+        })
+}
+
+fn junk_filter() -> ItemFilter {
+    ItemFilter::new().flags(ItemFlags::JUNK).excluded_flags(ItemFlags::FAVORITE)
+}
+
+/// Total price to buy the given number of items
+pub fn buy_total(gd: &GameData, il: ItemLocation, n: u32) -> i64 {
+    let item = gd.get_item(il).0;
+    buy_price(item, gd.chara.get(CharaId::Player), gd.get_shop(shop_cid(il))) * n as i64
+}
+
+/// Total price to sell the given number of items
+pub fn sell_total(gd: &GameData, il: ItemLocation, cid: CharaId, n: u32) -> i64 {
+    let item = gd.get_item(il).0;
+    sell_price(item, gd.chara.get(CharaId::Player), gd.get_shop(cid)) * n as i64
+}
+
+fn shop_cid(il: ItemLocation) -> CharaId {
+    match il.0 {
+        ItemListLocation::Shop { cid } => cid,
+        _ => unreachable!("Tried to buy an item not in a shop"),
+    }
 }
 
-/// Update items on a shop
-pub fn update_items_on_shop(shop: &mut Shop) {
-    shop.items.clear();
+/// Item price when the chara buys it. Trading skill decreases the price.
+/// Supply of other items of the same kind also decreases the price.
+/// Items sold by the player are sold back at a markup.
+pub fn buy_price(item: &Item, chara: &Chara, shop: &Shop) -> i64 {
+    if let Some(price) = item.buyback_price() {
+        let price = price as f32 * RULES.town.buyback_price_rate;
+        return ::std::cmp::max(price as i64, 1);
+    }
+    let price = item.price() as f32 * (1.0 - chara.skill_effect(skill_id::TRADING))
+        * supply_price_rate(shop.supply(item.idx).saturating_sub(1));
+    ::std::cmp::max(price as i64, 1)
+}
+
+/// Item price when the chara sells it. Trading skill increases the price.
+/// Items of abundant kinds in the shop and unidentified items are sold cheaply.
+pub fn sell_price(item: &Item, chara: &Chara, shop: &Shop) -> i64 {
+    let price = item.selling_price() as f32 * (1.0 + chara.skill_effect(skill_id::TRADING))
+        * supply_price_rate(shop.supply(item.idx));
+    if super::item::identify::is_identified(item) {
+        price as i64
+    } else {
+        (price * RULES.identify.unidentified_price_rate) as i64
+    }
+}
+
+fn supply_price_rate(supply: u32) -> f32 {
+    let r = &RULES.town;
+    let rate = 1.0 - supply as f32 * r.supply_price_rate;
+    if rate < r.min_supply_price_rate { r.min_supply_price_rate } else { rate }
+}
 
-    let n_gen_item = ::rng::gen_range(RULES.town.min_shop_items, RULES.town.max_shop_items);
-    
-    for _ in 0..n_gen_item {
-        shop.items.append(gen_shop_item(shop.level, &shop.kind), 1);
+/// Trading at shops makes the town prosperous
+fn add_prosperity(gd: &mut GameData, cid: CharaId, money: i64) {
+    let sid = match cid {
+        CharaId::OnSite { sid, .. } => sid,
+        _ => { return; }
+    };
+    if let SiteContent::Town { ref mut town } = gd.region.get_site_mut(sid).content {
+        town.add_trading_money(money, RULES.town.money_per_prosperity);
     }
 }
 
-/// Generate new item at shops
-fn gen_shop_item(floor_level: u32, shop_kind: &ShopKind) -> Item {
+/// Shop level by the town prosperity
+pub fn shop_level(prosperity: u32) -> u32 {
+    1 + prosperity / RULES.town.prosperity_per_shop_level
+}
+
+/// Restock items on a shop. Some of the items in stock are kept.
+/// Shops only offering services are not restocked.
+pub fn restock_shop(shop: &mut Shop, today: u32) {
+    let r = &RULES.town;
+    if !shop.kind.sells_items() {
+        return;
+    }
+    shop.items.items.retain(|_| ::rng::get_rng().gen_bool(r.stock_keep_probability));
+
+    let n_gen_item = ::rng::gen_range(r.min_shop_items, r.max_shop_items);
+    for _ in (shop.items.len() as u32)..n_gen_item {
+        // Stop restocking if no item is available for this shop
+        let item = if let Some(item) = gen_shop_item(shop.level, &shop.kind) { item } else { break; };
+        shop.items.append(item, 1);
+    }
+
+    let money = r.shop_money_per_level * shop.level as i64;
+    if shop.money < money {
+        shop.money = money;
+    }
+    for n in shop.sold.values_mut() {
+        *n = (*n as f32 * r.sold_decay_rate) as u32;
+    }
+    shop.sold.retain(|_, n| *n > 0);
+    shop.restock_day = today + r.restock_days;
+}
+
+/// Fee for the service. Services using item windows take fees for each item.
+pub fn service_fee(gd: &GameData, kind: ServiceKind) -> i64 {
+    match kind {
+        ServiceKind::Heal => {
+            let level = gd.chara.get(CharaId::Player).base_params.level.level() as i64;
+            RULES.town.heal_fee_per_level * level
+        }
+        ServiceKind::Inn => RULES.town.inn_fee,
+        _ => 0,
+    }
+}
+
+/// The player pays the fee to the shop. Returns false if the player does not have enough money.
+pub fn pay_fee(gd: &mut GameData, cid: CharaId, fee: i64) -> bool {
+    if !gd.player.has_money(fee) {
+        game_log_i!("shop-lack-of-money"; chara=gd.chara.get(CharaId::Player));
+        return false;
+    }
+    gd.player.sub_money(fee);
+    gd.get_shop_mut(cid).money += fee;
+    true
+}
+
+/// Restore the player's HP at a healer
+pub fn heal_at_shop(gd: &mut GameData, cid: CharaId) {
+    let fee = service_fee(gd, ServiceKind::Heal);
+    if !pay_fee(gd, cid, fee) { return; }
+
+    let player = gd.chara.get_mut(CharaId::Player);
+    player.hp = player.params.max_hp;
+    game_log_i!("shop-healed"; chara=player);
+}
+
+/// Stay at an inn. Time passes, and the player's HP and SP are restored.
+pub fn stay_at_inn(gd: &mut GameData, cid: CharaId) {
+    let fee = service_fee(gd, ServiceKind::Inn);
+    if !pay_fee(gd, cid, fee) { return; }
+
+    for _ in 0..RULES.town.inn_hours {
+        if gd.time.advance_by(60.0).day() {
+            super::site::lifecycle::on_day_changed(gd);
+        }
+    }
+    let player = gd.chara.get_mut(CharaId::Player);
+    player.hp = player.params.max_hp;
+    if player.sp < RULES.chara.default_sp {
+        player.sp = RULES.chara.default_sp;
+    }
+    game_log_i!("shop-inn-stay"; chara=player);
+}
+
+/// Generate new item at shops. Returns None if no item is available.
+fn gen_shop_item(floor_level: u32, shop_kind: &ShopKind) -> Option<Item> {
     let f = |item_obj: &ItemObject| {
         match shop_kind {
             ShopKind::Equipment => {
@@ -47,8 +249,35 @@ fn gen_shop_item(floor_level: u32, shop_kind: &ShopKind) -> Item {
                     _ => 0.0,
                 }
             }
+            ShopKind::Blacksmith => {
+                match item_obj.kind {
+                    ItemKind::Weapon(_) | ItemKind::Armor(_) | ItemKind::RepairKit => 1.0,
+                    _ => 0.0,
+                }
+            }
+            ShopKind::GeneralStore => {
+                match item_obj.kind {
+                    ItemKind::Object | ItemKind::Potion | ItemKind::Food
+                        | ItemKind::Container | ItemKind::RepairKit => 1.0,
+                    _ => 0.0,
+                }
+            }
+            ShopKind::Food => {
+                match item_obj.kind {
+                    ItemKind::Food => 1.0,
+                    _ => 0.0,
+                }
+            }
+            ShopKind::Material => {
+                match item_obj.kind {
+                    ItemKind::Material => 1.0,
+                    _ => 0.0,
+                }
+            }
+            ShopKind::Healer | ShopKind::Appraiser | ShopKind::Storage | ShopKind::Inn => 0.0,
         }
     };
-    gen_item_by_level(floor_level, f)
+    let idx = choose_item_by_floor_level(floor_level, f)?;
+    Some(gen_item_from_idx(idx, floor_level))
 }
 
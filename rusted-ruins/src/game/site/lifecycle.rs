@@ -0,0 +1,176 @@
+
+//! Expiration and spawning of sites, monster respawning, and unloading maps of left sites
+
+use std::fs;
+use std::path::PathBuf;
+use common::gamedata::*;
+use rng::{self, Rng};
+use rules::RULES;
+use game::dungeon_gen::N_NPC_PER_FLOOR;
+
+/// Called before the current map is switched
+pub fn before_switch_map(gd: &mut GameData, prev_mid: MapId, next_mid: MapId) {
+    let today = gd.time.days();
+    if !prev_mid.is_region_map() {
+        gd.region.get_map_mut(prev_mid).last_visit = Some(today);
+    }
+
+    if !next_mid.is_region_map() && gd.region.get_site(next_mid.sid()).is_unloaded() {
+        load_site(gd, next_mid.sid());
+    }
+}
+
+/// Called after the current map is switched and the player is located
+pub fn after_switch_map(gd: &mut GameData, prev_mid: MapId, next_mid: MapId) {
+    if next_mid.is_region_map() {
+        // Maps of the dungeon which the player left are not needed until the next visit
+        if !prev_mid.is_region_map() && is_autogen_dungeon(gd, prev_mid.sid()) {
+            unload_site(gd, prev_mid.sid());
+        }
+        return;
+    }
+
+    let sid = next_mid.sid();
+    let dungeon_kind = match gd.region.get_site(sid).content {
+        SiteContent::AutoGenDungeon { dungeon_kind, .. } => dungeon_kind,
+        _ => { return; }
+    };
+    let params = &RULES.dungeon_gen[&dungeon_kind];
+    let today = gd.time.days();
+
+    // Reaching the deepest floor clears the dungeon
+    let site = gd.region.get_site_mut(sid);
+    if site.cleared_day.is_none() && next_mid.floor() + 1 >= site.max_floor() {
+        site.cleared_day = Some(today);
+    }
+
+    let last_visit = gd.region.get_map_mut(next_mid).last_visit.take();
+    if let Some(last_visit) = last_visit {
+        if last_visit + params.respawn_days <= today {
+            // Respawning does not make hostile npcs more than the initial number
+            let n_hostile = count_hostile_npcs(gd, next_mid);
+            let n = rng::gen_range(params.n_respawn[0], params.n_respawn[1] + 1)
+                .min(N_NPC_PER_FLOOR.saturating_sub(n_hostile));
+            trace!("Respawn {} npcs on {:?}", n, next_mid);
+            ::game::map::gen_npcs(gd, next_mid, n, next_mid.floor());
+        }
+    }
+}
+
+/// Called when the date is changed
+pub fn on_day_changed(gd: &mut GameData) {
+    expire_sites(gd);
+
+    // Towns in all regions are updated even if the player is not there
+    for sid in gd.region.get_site_ids(SiteKind::Town).into_iter() {
+        ::game::town::update_shops(gd, sid);
+    }
+
+    if rng::get_rng().gen_bool(RULES.params.dungeon_spawn_probability) {
+        let rid = gd.get_current_mapid().rid();
+        ::game::region::gen_dungeon(gd, rid);
+    }
+}
+
+/// Remove cleared dungeons which have passed the expiration days
+fn expire_sites(gd: &mut GameData) {
+    let today = gd.time.days();
+    let current_mid = gd.get_current_mapid();
+    let rid = current_mid.rid();
+
+    for sid in gd.region.get(rid).get_site_ids(SiteKind::AutoGenDungeon).into_iter() {
+        if !current_mid.is_region_map() && current_mid.sid() == sid { continue; }
+
+        {
+            let site = gd.region.get_site(sid);
+            let expire_days = match site.content {
+                SiteContent::AutoGenDungeon { dungeon_kind, .. } => RULES.dungeon_gen[&dungeon_kind].expire_days,
+                _ => { continue; }
+            };
+            let expired = site.cleared_day.map_or(false, |cleared_day| cleared_day + expire_days <= today);
+            if !expired { continue; }
+        }
+
+        trace!("Site {:?} expired", sid);
+        let pos = gd.region.get_site_pos(sid);
+        gd.region.get_map_mut(MapId::from(rid)).tile[pos].special = SpecialTileKind::None;
+        gd.remove_site(sid);
+    }
+}
+
+fn count_hostile_npcs(gd: &GameData, mid: MapId) -> u32 {
+    let player = gd.chara.get(CharaId::Player);
+    gd.region.get_map(mid).iter_charaid()
+        .filter(|&&cid| cid != CharaId::Player)
+        .filter(|&&cid| player.rel.relative(gd.chara.get(cid).rel) == Relationship::HOSTILE)
+        .count() as u32
+}
+
+fn is_autogen_dungeon(gd: &GameData, sid: SiteId) -> bool {
+    gd.region.get_site(sid).content.kind() == SiteKind::AutoGenDungeon
+}
+
+/// The directory of unloaded site files for the save of this game
+fn unloaded_site_dir(gd: &GameData) -> PathBuf {
+    let mut path = ::config::abs_path("save");
+    path.push(&gd.save_id);
+    path.push("sites");
+    path
+}
+
+/// Write maps of the site to disk, and remove them from memory.
+/// Files of reloaded or removed sites are deleted here.
+fn unload_site(gd: &mut GameData, sid: SiteId) {
+    let file_name = format!("{}-{:08x}.msgpack", sid, rng::next_u32());
+    let path = unloaded_site_dir(gd).join(&file_name);
+    let unloaded = gd.unload_site(sid);
+
+    let result = path.parent().map_or(Ok(()), |dir| fs::create_dir_all(dir))
+        .map_err(|e| e.to_string())
+        .and_then(|_| fs::File::create(&path).map_err(|e| e.to_string()))
+        .and_then(|mut file| unloaded.write(&mut file).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        warn!("Failed to unload site to \"{}\": {}", path.display(), e);
+        gd.reload_site(sid, unloaded);
+    } else {
+        gd.region.get_site_mut(sid).unloaded_file = Some(file_name);
+        remove_unused_site_files(gd);
+    }
+}
+
+/// Restore maps of the site from disk.
+/// The file is deleted at the next unloading. If the file cannot be read, floors will be generated again.
+fn load_site(gd: &mut GameData, sid: SiteId) {
+    let file_name = gd.region.get_site_mut(sid).unloaded_file.take();
+    let path = unloaded_site_dir(gd).join(file_name.unwrap_or_default());
+
+    let result = fs::File::open(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| UnloadedSite::read(file).map_err(|e| e.to_string()));
+
+    match result {
+        Ok(unloaded) => {
+            gd.reload_site(sid, unloaded);
+        }
+        Err(e) => {
+            warn!("Failed to load site from \"{}\": {}", path.display(), e);
+            gd.reload_site(sid, UnloadedSite::default());
+        }
+    }
+}
+
+/// Remove unloaded site files which this game does not refer to
+fn remove_unused_site_files(gd: &GameData) {
+    let used: Vec<&str> = gd.region.iter_sites()
+        .filter_map(|site| site.unloaded_file.as_ref().map(|f| f.as_str()))
+        .collect();
+    let entries = if let Ok(entries) = fs::read_dir(unloaded_site_dir(gd)) { entries } else { return; };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let is_used = entry.file_name().to_str().map_or(false, |name| used.contains(&name));
+        if !is_used {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
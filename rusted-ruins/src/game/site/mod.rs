@@ -1,5 +1,6 @@
 
 pub mod gen;
+pub mod lifecycle;
 
 use common::gamedata::*;
 
@@ -0,0 +1,210 @@
+
+//! Functions for traps, doors, levers and containers
+
+use array2d::*;
+use rng;
+use common::gamedata::*;
+use super::{Game, InfoGetter};
+use super::extrait::*;
+use super::combat::DamageKind;
+use rules::RULES;
+
+/// Hidden traps in this range from the player can be detected
+const TRAP_DETECTION_RANGE: i32 = 2;
+
+/// Trigger the trap on the given tile if exists.
+/// Triggered traps are revealed.
+pub fn trigger_trap(game: &mut Game, cid: CharaId, pos: Vec2d) {
+    let kind = match game.gd.get_current_map().tile[pos].special {
+        SpecialTileKind::Trap { kind, .. } => kind,
+        _ => { return; }
+    };
+    game.gd.get_current_map_mut().tile[pos].special = SpecialTileKind::Trap { kind, hidden: false };
+    let floor = game.gd.get_current_mapid().floor();
+
+    {
+        let chara = game.gd.chara.get(cid);
+        game_log!("trap-triggered"; chara=chara, trap=kind);
+    }
+
+    match kind {
+        TrapKind::Damage => {
+            let damage = rng::dice(floor as i32 / 2 + 2, 6);
+            {
+                let chara = game.gd.chara.get(cid);
+                game_log!("trap-damage"; chara=chara, damage=damage);
+            }
+            super::chara::damage(game, cid, damage, DamageKind::Trap);
+        }
+        TrapKind::Poison => {
+            let chara = game.gd.chara.get_mut(cid);
+            let turn_left = 10 + floor as u16;
+            if chara.apply_status(CharaStatus::Poisoned { turn_left, strength: RULES.chara.poison_strength }) {
+                game_log!("poisoned"; chara=chara);
+            }
+        }
+        TrapKind::Spike => {
+            let chara = game.gd.chara.get_mut(cid);
+            let turn_left = 5 + floor as u16 / 2;
+            if chara.apply_status(CharaStatus::Bleeding { turn_left, strength: RULES.chara.bleeding_strength }) {
+                game_log!("start-bleeding"; chara=chara);
+            }
+        }
+        TrapKind::Fire => {
+            let chara = game.gd.chara.get_mut(cid);
+            let turn_left = 3 + floor as u16 / 3;
+            if chara.apply_status(CharaStatus::Burning { turn_left, strength: RULES.chara.burning_strength }) {
+                game_log!("catch-fire"; chara=chara);
+            }
+        }
+        TrapKind::Teleport => {
+            let map = game.gd.get_current_map_mut();
+            if let Some(p) = super::map::choose_empty_tile(map) {
+                map.locate_chara(cid, p);
+            }
+        }
+        TrapKind::Alarm => {
+            // Sleeping characters on this map wake up
+            let cids: Vec<CharaId> = game.gd.get_current_map().iter_charaid().cloned().collect();
+            for cid in cids.into_iter() {
+                game.gd.chara.get_mut(cid).status.retain(|s| match *s {
+                    CharaStatus::Asleep { .. } => false,
+                    _ => true,
+                });
+            }
+        }
+    }
+}
+
+/// The player may detect hidden traps around.
+/// The chance is given by the detection skill.
+pub fn detect_traps(game: &mut Game) {
+    if game.gd.get_current_mapid().is_region_map() { return; }
+
+    let player_pos = game.gd.player_pos();
+    let chance = game.gd.chara.get(CharaId::Player).skill_effect(skill_id::DETECTION);
+    let mut detected = false;
+    {
+        let map = game.gd.get_current_map_mut();
+        for (_, pos) in MDistRangeIter::new(player_pos, TRAP_DETECTION_RANGE) {
+            if !map.is_inside(pos) { continue; }
+            if let SpecialTileKind::Trap { kind, hidden: true } = map.tile[pos].special {
+                if rng::gen_range(0.0, 1.0) < chance {
+                    map.tile[pos].special = SpecialTileKind::Trap { kind, hidden: false };
+                    detected = true;
+                }
+            }
+        }
+    }
+
+    if detected {
+        let player = game.gd.chara.get_mut(CharaId::Player);
+        game_log!("trap-detected"; chara=player);
+        player.add_skill_use_exp(skill_id::DETECTION);
+    }
+}
+
+/// Process a character bumping into a closed door or a locked container.
+/// Returns true if the character spent the turn.
+pub fn bump_special_tile(game: &mut Game, cid: CharaId, pos: Vec2d) -> bool {
+    if !game.gd.get_current_map().is_inside(pos) { return false; }
+
+    match game.gd.get_current_map().tile[pos].special {
+        SpecialTileKind::Door { open: false, lock_level } => {
+            if lock_level > 0 {
+                // NPCs do not try to pick locks
+                if cid != CharaId::Player { return false; }
+                if !try_unlock(game, cid, lock_level) { return true; }
+            }
+            game.gd.get_current_map_mut().tile[pos].special
+                = SpecialTileKind::Door { open: true, lock_level: 0 };
+            let chara = game.gd.chara.get(cid);
+            game_log!("door-open"; chara=chara);
+            true
+        }
+        SpecialTileKind::Container { lock_level } if lock_level > 0 => {
+            if cid != CharaId::Player { return false; }
+            if try_unlock(game, cid, lock_level) {
+                game.gd.get_current_map_mut().tile[pos].special
+                    = SpecialTileKind::Container { lock_level: 0 };
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Try to unlock by lockpicking skill. Returns true if succeeded.
+fn try_unlock(game: &mut Game, cid: CharaId, lock_level: u16) -> bool {
+    let chara = game.gd.chara.get_mut(cid);
+    let chance = chara.skill_effect(skill_id::LOCKPICKING) + 0.3 - lock_level as f32 * 0.05;
+    let chance = if chance < 0.05 { 0.05 } else { chance };
+
+    chara.add_skill_use_exp(skill_id::LOCKPICKING);
+    if rng::gen_range(0.0, 1.0) < chance {
+        game_log!("unlock-success"; chara=chara);
+        true
+    } else {
+        game_log!("unlock-failure"; chara=chara);
+        false
+    }
+}
+
+/// Close an open door next to the character.
+/// Returns false if there is no door to close.
+pub fn close_door(game: &mut Game, cid: CharaId) -> bool {
+    let center = if let Some(p) = game.gd.get_current_map().chara_pos(cid) { p } else { return false; };
+
+    let door_pos = {
+        let map = game.gd.get_current_map();
+        Direction::EIGHT_DIRS.iter()
+            .map(|dir| center + dir.as_vec())
+            .find(|&p| {
+                if !map.is_inside(p) { return false; }
+                let tile = &map.tile[p];
+                match tile.special {
+                    SpecialTileKind::Door { open: true, .. } => tile.chara.is_none(),
+                    _ => false,
+                }
+            })
+    };
+
+    if let Some(p) = door_pos {
+        game.gd.get_current_map_mut().tile[p].special
+            = SpecialTileKind::Door { open: false, lock_level: 0 };
+        let chara = game.gd.chara.get(cid);
+        game_log!("door-close"; chara=chara);
+        true
+    } else {
+        false
+    }
+}
+
+/// Pull the lever on the given tile. The target door is opened or closed.
+/// Returns false if there is no lever.
+pub fn pull_lever(game: &mut Game, cid: CharaId, pos: Vec2d) -> bool {
+    {
+        let map = game.gd.get_current_map_mut();
+        let (on, target) = match map.tile[pos].special {
+            SpecialTileKind::Lever { on, target } => (!on, target),
+            _ => { return false; }
+        };
+        map.tile[pos].special = SpecialTileKind::Lever { on, target };
+
+        if map.is_inside(target) {
+            match map.tile[target].special {
+                SpecialTileKind::Door { .. } => {
+                    // A door cannot be closed on a character
+                    if on || map.tile[target].chara.is_none() {
+                        map.tile[target].special = SpecialTileKind::Door { open: on, lock_level: 0 };
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let chara = game.gd.chara.get(cid);
+    game_log!("pull-lever"; chara=chara);
+    true
+}
@@ -0,0 +1,102 @@
+
+//! Functions for spell casting
+
+use rng;
+use common::gobj;
+use common::objholder::SpellIdx;
+use common::gamedata::*;
+use rules::RULES;
+use super::Game;
+use super::extrait::*;
+use super::combat::DamageKind;
+
+/// Cast a spell to the target.
+/// If the caster actually do actions, returns true.
+pub fn cast_spell(game: &mut Game, caster: CharaId, spell_idx: SpellIdx, target: Option<CharaId>) -> bool {
+    let spell = gobj::get_obj(spell_idx);
+
+    let target = match spell.target {
+        SpellTarget::Caster => caster,
+        SpellTarget::Target => {
+            if let Some(target) = target {
+                target
+            } else {
+                game_log_i!("no-spell-target");
+                return false;
+            }
+        }
+    };
+
+    {
+        let caster = game.gd.chara.get_mut(caster);
+        if caster.mp < spell.cost {
+            game_log_i!("lack-of-mp"; chara=caster);
+            return false;
+        }
+        caster.mp -= spell.cost;
+        let spell_name = ::text::obj_txt(&spell.id);
+        game_log!("cast-spell"; chara=caster, spell=spell_name);
+    }
+
+    let skill_kind = SkillKind::Magic(spell.magic_kind);
+    let (power, target_level) = {
+        let caster = game.gd.chara.get(caster);
+        let target = game.gd.chara.get(target);
+        let skill_level = caster.skills.get(&skill_kind);
+        (calc_spell_power(spell.power, caster.params.int, skill_level), target.base_params.level.level())
+    };
+
+    match spell.effect {
+        SpellEffect::Damage => {
+            let damage = {
+                let target_chara = game.gd.chara.get(target);
+                let resistance = target_chara.params.wil as f64 + 8.0;
+                let damage = (power / resistance) as i32;
+                game_log!("spell-damage"; target=target_chara, damage=damage);
+                damage
+            };
+            super::chara::damage(game, target, damage, DamageKind::Spell);
+            super::chara::gain_kill_exp(game, caster, target);
+            if let Some(pos) = game.gd.get_current_map().chara_pos(target) {
+                game.anim_queue.push_attack(pos);
+            }
+        }
+        SpellEffect::Heal => {
+            use std::cmp::min;
+            let target = game.gd.chara.get_mut(target);
+            let value = power as i32 / 8;
+            target.hp = min(target.params.max_hp, target.hp + value);
+            game_log!("heal-hp"; chara=target, value=value);
+        }
+        SpellEffect::Sleep => {
+            let target = game.gd.chara.get_mut(target);
+            if target.apply_status(CharaStatus::Asleep { turn_left: spell.power }) {
+                game_log!("fall-asleep"; chara=target);
+            }
+        }
+        SpellEffect::Poison => {
+            let target = game.gd.chara.get_mut(target);
+            if target.apply_status(CharaStatus::Poisoned {
+                turn_left: spell.power, strength: RULES.chara.poison_strength }) {
+                game_log!("poisoned"; chara=target);
+            }
+        }
+    }
+
+    // Exp processing
+    {
+        let caster = game.gd.chara.get_mut(caster);
+        caster.add_skill_exp(skill_kind, RULES.exp.cast, target_level);
+    }
+    ::audio::play_sound("spell");
+    true
+}
+
+/// Calculate spell power from the spell's power, caster's int and magic skill level
+fn calc_spell_power(spell_power: u16, int: u16, skill_level: u16) -> f64 {
+    let dice = rng::dice(1, spell_power as i32 + 1) as f64;
+    let int = int as f64;
+    let skill_level = skill_level as f64;
+
+    dice * int * (skill_level + 8.0).sqrt()
+}
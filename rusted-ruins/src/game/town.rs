@@ -4,11 +4,13 @@ use common::obj::SiteGenObject;
 use common::gamedata::*;
 use common::gobj;
 use rules::RULES;
+use game::shop::{shop_level, restock_shop};
 
 /// Create town from SiteGenObect and add it to region map
 pub fn add_town(gd: &mut GameData, rid: RegionId, pos: Vec2d, town_id: &str) {
     let sg: &SiteGenObject = gobj::get_by_id(town_id);
-    let town = Town::new(town_id);
+    let mut town = Town::new(town_id);
+    town.add_prosperity(RULES.town.initial_prosperity);
     let mut site = Site::new(sg.map_template_id.len() as u32);
     let site_content = SiteContent::Town { town: Box::new(town) };
     site.content = site_content;
@@ -41,22 +43,19 @@ pub fn add_town(gd: &mut GameData, rid: RegionId, pos: Vec2d, town_id: &str) {
             _ => unreachable!(),
         };
         
+        let level = shop_level(town.prosperity());
         for shop_gen_data in &sg.shops {
-            let shop = Shop {
-                kind: shop_gen_data.kind,
-                items: ItemList::new(),
-                level: 1,
-            };
+            let shop = Shop::new(shop_gen_data.kind, level);
             town.add_shop(shop, shop_gen_data.chara_n);
         }
     }
     update_shops(gd, sid);
 }
 
-/// Update shop states
+/// Restock shops which have reached their restocking day.
+/// Shop levels are updated by the town prosperity.
 pub fn update_shops(gd: &mut GameData, sid: SiteId) {
-    use game::shop::update_items_on_shop;
-    
+    let today = gd.time.days();
     let site = gd.region.get_site_mut(sid);
     let town = match &mut site.content {
         SiteContent::Town { ref mut town } => town,
@@ -66,8 +65,12 @@ pub fn update_shops(gd: &mut GameData, sid: SiteId) {
         }
     };
 
+    let level = shop_level(town.prosperity());
     for shop in town.iter_shops_mut() {
-        update_items_on_shop(shop)
+        if shop.restock_day <= today {
+            shop.level = level;
+            restock_shop(shop, today);
+        }
     }
 }
 
@@ -7,6 +7,7 @@ use text::ToText;
 use super::{Game, GameState};
 use super::chara::preturn::preturn;
 use super::npc::process_npc_turn;
+use super::special_tile::detect_traps;
 use super::DialogOpenRequest;
 
 /// Game time will advance when clock_count reaches this
@@ -74,6 +75,7 @@ pub fn turn_loop(game: &mut Game) {
         // If player's wait time becomes 0, player turn now.
         if decrease_wait_time(&mut game.gd.chara.get_mut(CharaId::Player)) {
             if preturn(game, CharaId::Player) {
+                detect_traps(game);
                 game.state = GameState::PlayerTurn;
                 game.update_before_player_turn();
                 break;
@@ -131,6 +133,9 @@ fn advance_game_date(game: &mut Game) {
         RULES.params.minutes_per_turn_normal
     };
 
-    let _changed = game.gd.time.advance_by(advance_minutes);
+    let changed = game.gd.time.advance_by(advance_minutes);
+    if changed.day() {
+        super::site::lifecycle::on_day_changed(&mut game.gd);
+    }
 }
 
@@ -66,7 +66,7 @@ pub fn update_view_map(game: &mut Game) {
 
         for p in LineIter::new(player_pos, pos).skip(1) {
             view_map.visible[p] = true;
-            if !map.tile[p].wall.is_empty() {
+            if !map.tile[p].wall.is_empty() || map.tile[p].special.is_blocking_view() {
                 break;
             }
         }
@@ -75,7 +75,7 @@ pub fn update_view_map(game: &mut Game) {
 
 pub fn calc_visual_distance(map: &Map, orig: Vec2d, dist: Vec2d) -> Option<i32> {
     for pos in LineIter::new(orig, dist) {
-        if !map.tile[pos].wall.is_empty() {
+        if !map.tile[pos].wall.is_empty() || map.tile[pos].special.is_blocking_view() {
             return None;
         }
     }
@@ -2,6 +2,9 @@
 use super::ToTextId;
     
 use common::gamedata::*;
+use game::danger::DangerLevel;
+use game::item::filter::ItemSortKind;
+use rules::affix::AttrKind;
 
 impl ToTextId for DungeonKind {
     fn to_textid(&self) -> &'static str {
@@ -20,21 +23,41 @@ impl ToTextId for CharaStatus {
             CharaStatus::Weak          => "!chara_status.weak",
             CharaStatus::Starving      => "!chara_status.starving",
             CharaStatus::Asleep { .. } => "!chara_status.asleep",
-            CharaStatus::Poisoned      => "!chara_status.poisoned",
+            CharaStatus::Poisoned { .. }  => "!chara_status.poisoned",
+            CharaStatus::Confused { .. }  => "!chara_status.confused",
+            CharaStatus::Paralyzed { .. } => "!chara_status.paralyzed",
+            CharaStatus::Blind { .. }     => "!chara_status.blind",
+            CharaStatus::Bleeding { .. }  => "!chara_status.bleeding",
+            CharaStatus::Burning { .. }   => "!chara_status.burning",
+            CharaStatus::Hasted { .. }    => "!chara_status.hasted",
+            CharaStatus::Burdened      => "!chara_status.burdened",
+            CharaStatus::Stressed      => "!chara_status.stressed",
+            CharaStatus::Overloaded    => "!chara_status.overloaded",
         }
     }
 }
 
-impl ToTextId for SkillKind {
-   fn to_textid(&self) -> &'static str {
-       match self {
-           SkillKind::MartialArts   => "!skill_kind.martial_arts",
-           SkillKind::Defence       => "!skill_kind.defence",
-           SkillKind::Weapon(weapon_kind) => {
-               weapon_kind.to_textid()
-           }
-       }
-   }
+impl ToTextId for MagicKind {
+    fn to_textid(&self) -> &'static str {
+        match self {
+            MagicKind::Destruction => "!magic_kind.destruction",
+            MagicKind::Restoration => "!magic_kind.restoration",
+            MagicKind::Enchantment => "!magic_kind.enchantment",
+        }
+    }
+}
+
+impl ToTextId for TrapKind {
+    fn to_textid(&self) -> &'static str {
+        match self {
+            TrapKind::Damage   => "!trap_kind.damage",
+            TrapKind::Poison   => "!trap_kind.poison",
+            TrapKind::Teleport => "!trap_kind.teleport",
+            TrapKind::Alarm    => "!trap_kind.alarm",
+            TrapKind::Spike    => "!trap_kind.spike",
+            TrapKind::Fire     => "!trap_kind.fire",
+        }
+    }
 }
 
 impl ToTextId for WeaponKind {
@@ -50,3 +73,99 @@ impl ToTextId for WeaponKind {
         }
     }
 }
+
+impl ToTextId for DangerLevel {
+    fn to_textid(&self) -> &'static str {
+        match *self {
+            DangerLevel::Safe     => "!danger_level.safe",
+            DangerLevel::Low      => "!danger_level.low",
+            DangerLevel::Moderate => "!danger_level.moderate",
+            DangerLevel::High     => "!danger_level.high",
+            DangerLevel::Deadly   => "!danger_level.deadly",
+        }
+    }
+}
+
+impl ToTextId for Element {
+    fn to_textid(&self) -> &'static str {
+        match *self {
+            Element::None     => "!element.none",
+            Element::Physical => "!element.physical",
+            Element::Fire     => "!element.fire",
+            Element::Cold     => "!element.cold",
+            Element::Shock    => "!element.shock",
+            Element::Poison   => "!element.poison",
+            Element::Spirit   => "!element.spirit",
+        }
+    }
+}
+
+impl ToTextId for AttrKind {
+    fn to_textid(&self) -> &'static str {
+        match *self {
+            AttrKind::Str => "!attr_kind.str",
+            AttrKind::Vit => "!attr_kind.vit",
+            AttrKind::Dex => "!attr_kind.dex",
+            AttrKind::Int => "!attr_kind.int",
+            AttrKind::Wil => "!attr_kind.wil",
+            AttrKind::Cha => "!attr_kind.cha",
+            AttrKind::Spd => "!attr_kind.spd",
+        }
+    }
+}
+
+impl ToTextId for ShopKind {
+    fn to_textid(&self) -> &'static str {
+        match *self {
+            ShopKind::Equipment    => "!shop_kind.equipment",
+            ShopKind::Potion       => "!shop_kind.potion",
+            ShopKind::Blacksmith   => "!shop_kind.blacksmith",
+            ShopKind::GeneralStore => "!shop_kind.general_store",
+            ShopKind::Food         => "!shop_kind.food",
+            ShopKind::Material     => "!shop_kind.material",
+            ShopKind::Healer       => "!shop_kind.healer",
+            ShopKind::Appraiser    => "!shop_kind.appraiser",
+            ShopKind::Storage      => "!shop_kind.storage",
+            ShopKind::Inn          => "!shop_kind.inn",
+        }
+    }
+}
+
+impl ToTextId for ServiceKind {
+    fn to_textid(&self) -> &'static str {
+        match *self {
+            ServiceKind::Heal     => "!service_kind.heal",
+            ServiceKind::Identify => "!service_kind.identify",
+            ServiceKind::Repair   => "!service_kind.repair",
+            ServiceKind::Storage  => "!service_kind.storage",
+            ServiceKind::Inn      => "!service_kind.inn",
+        }
+    }
+}
+
+impl ToTextId for ItemKindRough {
+    fn to_textid(&self) -> &'static str {
+        match *self {
+            ItemKindRough::Object    => "!item_kind_rough.object",
+            ItemKindRough::Potion    => "!item_kind_rough.potion",
+            ItemKindRough::Food      => "!item_kind_rough.food",
+            ItemKindRough::Weapon    => "!item_kind_rough.weapon",
+            ItemKindRough::Armor     => "!item_kind_rough.armor",
+            ItemKindRough::Material  => "!item_kind_rough.material",
+            ItemKindRough::Container => "!item_kind_rough.container",
+            ItemKindRough::RepairKit => "!item_kind_rough.repair_kit",
+            ItemKindRough::Special   => "!item_kind_rough.special",
+        }
+    }
+}
+
+impl ToTextId for ItemSortKind {
+    fn to_textid(&self) -> &'static str {
+        match *self {
+            ItemSortKind::Kind   => "!item_sort_kind.kind",
+            ItemSortKind::Weight => "!item_sort_kind.weight",
+            ItemSortKind::Price  => "!item_sort_kind.price",
+            ItemSortKind::Name   => "!item_sort_kind.name",
+        }
+    }
+}
@@ -2,6 +2,8 @@
 use std::borrow::Cow;
 use common::gamedata::*;
 use common::gobj;
+use game::item::identify;
+use rules::RULES;
 use text::{self, ToText, ToTextId};
 
 impl<T: ToTextId> ToText for T {
@@ -18,7 +20,13 @@ impl ToText for Site {
         }
         
         match self.content {
-            SiteContent::AutoGenDungeon { dungeon_kind } => {
+            SiteContent::AutoGenDungeon { dungeon_kind, ref theme } => {
+                if let Some(ref theme) = *theme {
+                    let id = format!("!dungeon_theme.{}", theme);
+                    if let Some(txt) = text::misc_txt_checked(&id) {
+                        return txt.into();
+                    }
+                }
                 text::to_txt(&dungeon_kind).into()
             }
             SiteContent::Town { ref town } => {
@@ -32,9 +40,46 @@ impl ToText for Site {
     }
 }
 
+impl ToText for SkillKind {
+    fn to_text(&self) -> Cow<str> {
+        match *self {
+            SkillKind::MartialArts => text::misc_txt("!skill_kind.martial_arts").into(),
+            SkillKind::Defence => text::misc_txt("!skill_kind.defence").into(),
+            SkillKind::Weapon(weapon_kind) => text::to_txt(&weapon_kind).into(),
+            SkillKind::Magic(magic_kind) => text::to_txt(&magic_kind).into(),
+            SkillKind::NonCombat(ref id) => {
+                let textid = format!("!skill_kind.{}", id);
+                text::misc_txt_checked(&textid).unwrap_or(id).into()
+            }
+        }
+    }
+}
+
 impl ToText for Item {
     fn to_text(&self) -> Cow<str> {
-        ::text::obj_txt(gobj::idx_to_id(self.idx)).into()
+        if let Some(appearance) = identify::appearance(self) {
+            let textid = format!("!appearance.{}", appearance);
+            return text::misc_txt_checked(&textid).map(|s| s.to_owned()).unwrap_or(appearance).into();
+        }
+        let name = ::text::obj_txt(gobj::idx_to_id(self.idx));
+        let affixes = self.affixes();
+        if affixes.is_empty() || !identify::is_identified(self) {
+            return name.into();
+        }
+
+        let affix_txt = |id: &str| {
+            let textid = format!("!affix.{}", id);
+            text::misc_txt_checked(&textid).unwrap_or(id).to_owned()
+        };
+        let mut words: Vec<String> = affixes.iter()
+            .filter(|id| RULES.affix.is_prefix(id))
+            .map(|id| affix_txt(id))
+            .collect();
+        words.push(name.to_owned());
+        words.extend(affixes.iter()
+                     .filter(|id| !RULES.affix.is_prefix(id))
+                     .map(|id| affix_txt(id)));
+        words.join(" ").into()
     }
 }
 
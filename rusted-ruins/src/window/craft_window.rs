@@ -0,0 +1,92 @@
+use config::UI_CFG;
+use common::gobj;
+use common::objholder::RecipeIdx;
+use game::craft;
+use super::commonuse::*;
+use super::widget::*;
+use text;
+
+/// Player chooses a recipe to craft
+pub struct CraftWindow {
+    rect: Rect,
+    list: ListWidget,
+    recipes: Vec<RecipeIdx>,
+}
+
+impl CraftWindow {
+    pub fn new(pa: &mut DoPlayerAction) -> CraftWindow {
+        let rect = UI_CFG.craft_window.rect.into();
+
+        let mut craft_window = CraftWindow {
+            rect: rect,
+            list: ListWidget::new(
+                (0i32, 0i32, rect.w as u32, rect.h as u32), ListRowKind::Str,
+                vec![0], Some(UI_CFG.craft_window.n_row), 26),
+            recipes: craft::available_recipes(pa.gd()),
+        };
+        craft_window.update_list(pa);
+        craft_window
+    }
+
+    fn update_list(&mut self, pa: &mut DoPlayerAction) {
+        let gd = pa.gd();
+        self.list.set_n_item(self.recipes.len() as u32);
+        let recipes = &self.recipes;
+
+        self.list.update_rows_by_func(|start, page_size| {
+            let mut rows = Vec::new();
+            for recipe_idx in recipes.iter().skip(start as usize).take(page_size as usize) {
+                let recipe = gobj::get_obj(*recipe_idx);
+                let inputs = recipe.inputs.iter()
+                    .map(|input| format!("{} x{}", text::obj_txt(&input.id), input.n))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mark = if craft::has_inputs(gd, recipe) { "" } else { "- " };
+                let s = format!("{}{} x{}  ({})",
+                                mark, text::obj_txt(&recipe.output), recipe.n_output, inputs);
+                rows.push(ListRow::Str(s));
+            }
+            rows
+        });
+    }
+}
+
+impl Window for CraftWindow {
+    fn draw(
+        &mut self, canvas: &mut WindowCanvas, _game: &Game, sv: &mut SdlValues,
+        _anim: Option<(&Animation, u32)>) {
+
+        draw_rect_border(canvas, self.rect);
+        self.list.draw(canvas, sv);
+    }
+}
+
+impl DialogWindow for CraftWindow {
+    fn process_command(&mut self, command: &Command, pa: &mut DoPlayerAction) -> DialogResult {
+        if let Some(response) = self.list.process_command(&command) {
+            match response {
+                ListWidgetResponse::Select(i) => { // Any recipe is selected
+                    let i = self.list.get_page() * UI_CFG.craft_window.n_row + i;
+                    pa.craft(self.recipes[i as usize]);
+                    return DialogResult::Close;
+                }
+                ListWidgetResponse::PageChanged => {
+                    self.update_list(pa);
+                }
+                _ => (),
+            }
+            return DialogResult::Continue;
+        }
+
+        match *command {
+            Command::Cancel => {
+                DialogResult::Close
+            }
+            _ => DialogResult::Continue,
+        }
+    }
+
+    fn mode(&self) -> InputMode {
+        InputMode::Dialog
+    }
+}
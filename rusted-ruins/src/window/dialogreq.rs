@@ -1,5 +1,5 @@
 
-use common::gamedata::CharaId;
+use common::gamedata::{CharaId, ServiceKind};
 use game::{Game, DialogOpenRequest, DoPlayerAction, TalkText};
 use super::DialogWindow;
 use super::talk_window;
@@ -25,9 +25,38 @@ pub fn create_dialog_from_request(req: DialogOpenRequest, game: &mut Game) -> Op
             let mut pa = DoPlayerAction::new(game);
             Box::new(ItemWindow::new(ItemWindowMode::ShopBuy { cid }, &mut pa))
         }
-        DialogOpenRequest::ShopSell => {
+        DialogOpenRequest::ShopSell { cid } => {
             let mut pa = DoPlayerAction::new(game);
-            Box::new(ItemWindow::new(ItemWindowMode::ShopSell, &mut pa))
+            Box::new(ItemWindow::new(ItemWindowMode::ShopSell { cid }, &mut pa))
+        }
+        DialogOpenRequest::ShopService { cid, kind } => {
+            let mut pa = DoPlayerAction::new(game);
+            match kind {
+                ServiceKind::Identify => {
+                    Box::new(ItemWindow::new(ItemWindowMode::ShopIdentify { cid }, &mut pa))
+                }
+                ServiceKind::Repair => {
+                    Box::new(ItemWindow::new(ItemWindowMode::ShopRepair { cid }, &mut pa))
+                }
+                ServiceKind::Storage => {
+                    Box::new(ItemWindow::new(ItemWindowMode::StorageTakeOut { cid }, &mut pa))
+                }
+                ServiceKind::Heal | ServiceKind::Inn => {
+                    let fee = ::game::shop::service_fee(pa.gd(), kind);
+                    let msg = replace_str!(
+                        ::text::ui_txt("dialog.shop_service"); service=kind, fee=fee);
+                    let msgdialog = msg_dialog::MsgDialog::with_yesno(
+                        &msg,
+                        move |pa, n| {
+                            if n == 0 {
+                                pa.use_shop_service(cid, kind);
+                            }
+                            super::DialogResult::Close
+                        }
+                    );
+                    Box::new(msgdialog)
+                }
+            }
         }
         DialogOpenRequest::GameOver => {
             Box::new(super::exit_window::GameOverWindow::new())
@@ -0,0 +1,115 @@
+
+use super::commonuse::*;
+use super::widget::*;
+use sdlvalues::FontKind;
+use config::UI_CFG;
+use common::gamedata::*;
+use game::extrait::*;
+use rules::RULES;
+use rules::affix::AffixEffect;
+use text::{self, ToText};
+
+/// Item detail viewer
+pub struct ItemInfoWindow {
+    rect: Rect,
+    label: LineSpecifiedLabelWidget,
+}
+
+impl ItemInfoWindow {
+    pub fn new(item: &Item) -> ItemInfoWindow {
+        let rect: Rect = UI_CFG.item_info_window.rect.into();
+        let lines = item_info_lines(item);
+        let label = LineSpecifiedLabelWidget::new(
+            Rect::new(0, 0, rect.width(), rect.height()),
+            &lines, FontKind::M, UI_CFG.item_info_window.n_max_line);
+        ItemInfoWindow {
+            rect,
+            label,
+        }
+    }
+}
+
+fn item_info_lines(item: &Item) -> Vec<String> {
+    let mut lines = vec![item.to_text().into_owned()];
+
+    let weight = format!("{:.2}", item.w() as f32 / 1000.0);
+    lines.push(replace_str!(text::ui_txt("item_info.weight"); weight=weight));
+
+    match item.kind {
+        ItemKind::Weapon(_) => {
+            let (dice_n, dice_x) = item.dice();
+            let dice = format!("{}d{}", dice_n, dice_x);
+            lines.push(replace_str!(text::ui_txt("item_info.damage"); dice=dice));
+        }
+        ItemKind::Armor(_) => {
+            let def = item.def()[Element::Physical];
+            lines.push(replace_str!(text::ui_txt("item_info.defence"); def=def));
+        }
+        _ => (),
+    }
+
+    if item.rank.damage < 0 {
+        let damage = -item.rank.damage;
+        lines.push(replace_str!(text::ui_txt("item_info.damaged"); damage=damage));
+    }
+
+    if !::game::item::identify::is_identified(item) {
+        lines.push(text::ui_txt("item_info.unidentified").to_owned());
+        return lines;
+    }
+
+    let rank = item.rank.as_int();
+    lines.push(replace_str!(text::ui_txt("item_info.rank"); rank=rank));
+    for id in item.affixes() {
+        if let Some(affix) = RULES.affix.get(id) {
+            for effect in &affix.effects {
+                lines.push(affix_effect_text(effect));
+            }
+        }
+    }
+
+    lines
+}
+
+fn affix_effect_text(effect: &AffixEffect) -> String {
+    match *effect {
+        AffixEffect::Attribute { attr, value } => {
+            replace_str!(text::ui_txt("item_info.affix.attribute"); attr=attr, value=value)
+        }
+        AffixEffect::ElementalDamage { element, value } => {
+            replace_str!(text::ui_txt("item_info.affix.elemental_damage"); element=element, value=value)
+        }
+        AffixEffect::Resistance { element, value } => {
+            replace_str!(text::ui_txt("item_info.affix.resistance"); element=element, value=value)
+        }
+        AffixEffect::Skill { ref skill, value } => {
+            replace_str!(text::ui_txt("item_info.affix.skill"); skill=skill, value=value)
+        }
+        AffixEffect::Lifesteal { percent } => {
+            replace_str!(text::ui_txt("item_info.affix.lifesteal"); value=percent)
+        }
+    }
+}
+
+impl Window for ItemInfoWindow {
+    fn draw(
+        &mut self, canvas: &mut WindowCanvas, _game: &Game, sv: &mut SdlValues,
+        _anim: Option<(&Animation, u32)>) {
+
+        draw_rect_border(canvas, self.rect);
+        self.label.draw(canvas, sv);
+    }
+}
+
+impl DialogWindow for ItemInfoWindow {
+    fn process_command(&mut self, command: &Command, _pa: &mut DoPlayerAction) -> DialogResult {
+        match *command {
+            Command::Cancel => DialogResult::Close,
+            _ => DialogResult::Continue,
+        }
+    }
+
+    fn mode(&self) -> InputMode {
+        InputMode::Dialog
+    }
+}
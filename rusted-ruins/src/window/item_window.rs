@@ -1,4 +1,6 @@
 
+use std::any::Any;
+use std::borrow::Cow;
 use window::{Window, DialogWindow, DialogResult, WindowDrawMode};
 use sdl2::render::WindowCanvas;
 use sdl2::rect::Rect;
@@ -11,15 +13,57 @@ use draw::border::draw_rect_border;
 use eventhandler::InputMode;
 use super::widget::*;
 use super::misc_window::PageWindow;
+use super::item_info_window::ItemInfoWindow;
+use super::msg_dialog::MsgDialog;
+use super::quantity_dialog::QuantityDialog;
+use super::status_window::weight_text;
 use common::gamedata::*;
 use game::item::filter::*;
 
+/// Tabs to filter items by kind. None shows all items.
+static ITEM_TABS: [Option<ItemKindRough>; 9] = [
+    None,
+    Some(ItemKindRough::Weapon),
+    Some(ItemKindRough::Armor),
+    Some(ItemKindRough::Potion),
+    Some(ItemKindRough::Food),
+    Some(ItemKindRough::Material),
+    Some(ItemKindRough::Container),
+    Some(ItemKindRough::RepairKit),
+    Some(ItemKindRough::Object),
+];
+
 pub type ActionCallback = FnMut(&mut DoPlayerAction, ItemLocation) -> DialogResult;
 pub enum ItemWindowMode {
-    List, PickUp, Drop, Drink, Eat, ShopSell,
+    List, PickUp, Drop, Drink, Eat,
+    ShopIdentify {
+        cid: CharaId,
+    },
+    ShopRepair {
+        cid: CharaId,
+    },
     ShopBuy {
         cid: CharaId,
     },
+    ShopSell {
+        cid: CharaId,
+    },
+    /// Take items out of the storage of the shopkeeper
+    StorageTakeOut {
+        cid: CharaId,
+    },
+    /// Put player's items into the storage of the shopkeeper
+    StoragePutIn {
+        cid: CharaId,
+    },
+    /// Take items out of the container
+    ContainerTakeOut {
+        ill: ItemListLocation,
+    },
+    /// Put player's items into the container
+    ContainerPutIn {
+        ill: ItemListLocation,
+    },
     Select {
         ill: ItemListLocation,
         filter: ItemFilter,
@@ -33,6 +77,17 @@ pub struct ItemWindow {
     mode: ItemWindowMode,
     item_locations: Vec<ItemLocation>,
     page_window: PageWindow,
+    weight_label: LabelWidget,
+    tab_label: LabelWidget,
+    /// Index of ITEM_TABS
+    tab: usize,
+    sort_kind: ItemSortKind,
+    /// The item waiting for quantity selection to buy or sell
+    trading_item: Option<ItemLocation>,
+    /// The item waiting for quantity selection to split
+    splitting_item: Option<ItemLocation>,
+    /// The item to be selected after updating the list
+    item_to_select: Option<ItemLocation>,
 }
 
 impl ItemWindow {
@@ -48,6 +103,13 @@ impl ItemWindow {
             mode: mode,
             item_locations: Vec::new(),
             page_window: PageWindow::new(None, Some(rect.bottom() + UI_CFG.page_window.margin_to_parent)),
+            weight_label: LabelWidget::new(UI_CFG.item_window.weight_label_rect, "", FontKind::MonoM),
+            tab_label: LabelWidget::new(UI_CFG.item_window.tab_label_rect, "", FontKind::M),
+            tab: 0,
+            sort_kind: ItemSortKind::Kind,
+            trading_item: None,
+            splitting_item: None,
+            item_to_select: None,
         };
         item_window.update_by_mode(pa);
         item_window
@@ -63,59 +125,64 @@ impl ItemWindow {
 
     fn update_by_mode(&mut self, pa: &mut DoPlayerAction) {
         let gd = pa.gd();
+        let player_ill = ItemListLocation::Chara { cid: CharaId::Player };
         
-        match self.mode {
-            ItemWindowMode::List => {
-                let ill = ItemListLocation::Chara { cid: CharaId::Player };
-                let filtered_list = gd.get_filtered_item_list(ill, ItemFilter::all());
-                self.update_list(filtered_list);
-            }
+        let (ill, filter) = match self.mode {
+            ItemWindowMode::List => (player_ill, ItemFilter::all()),
             ItemWindowMode::PickUp => {
                 let ill = ItemListLocation::OnMap {
                     mid: gd.get_current_mapid(),
                     pos: gd.player_pos(),
                 };
-                let filtered_list = gd.get_filtered_item_list(ill, ItemFilter::all());
-                self.update_list(filtered_list);
-            }
-            ItemWindowMode::Drop => {
-                let ill = ItemListLocation::Chara { cid: CharaId::Player };
-                let filtered_list = gd.get_filtered_item_list(ill, ItemFilter::all());
-                self.update_list(filtered_list);
-            }
-            ItemWindowMode::Drink => {
-                let ill = ItemListLocation::Chara { cid: CharaId::Player };
-                let filtered_list = gd
-                    .get_filtered_item_list(ill, ItemFilter::new().flags(ItemFlags::DRINKABLE));
-                self.update_list(filtered_list);
+                (ill, ItemFilter::all())
             }
-            ItemWindowMode::Eat => {
-                let ill = ItemListLocation::Chara { cid: CharaId::Player };
-                let filtered_list = gd
-                    .get_filtered_item_list(ill, ItemFilter::new().flags(ItemFlags::EATABLE));
-                self.update_list(filtered_list);
-            }
-            ItemWindowMode::ShopBuy { cid } => {
-                let ill = ItemListLocation::Shop { cid };
-                let filtered_list = gd.get_filtered_item_list(ill, ItemFilter::new());
-                self.update_list(filtered_list);
-            }
-            ItemWindowMode::ShopSell => {
-                let ill = ItemListLocation::Chara { cid: CharaId::Player };
-                let filtered_list = gd.get_filtered_item_list(ill, ItemFilter::new());
-                self.update_list(filtered_list);
-            }
-            ItemWindowMode::Select { ill, filter, ..} => {
-                let filtered_list = gd.get_filtered_item_list(ill, filter);
-                self.update_list(filtered_list);
+            ItemWindowMode::Drop => (player_ill, ItemFilter::all()),
+            ItemWindowMode::Drink => (player_ill, ItemFilter::new().flags(ItemFlags::DRINKABLE)),
+            ItemWindowMode::Eat => (player_ill, ItemFilter::new().flags(ItemFlags::EATABLE)),
+            ItemWindowMode::ShopBuy { cid } => (ItemListLocation::Shop { cid }, ItemFilter::new()),
+            ItemWindowMode::ShopSell { .. } => (player_ill, ItemFilter::new()),
+            ItemWindowMode::ShopIdentify { .. } => (player_ill, ItemFilter::new().unidentified()),
+            ItemWindowMode::ShopRepair { .. } => {
+                (ItemListLocation::Equip { cid: CharaId::Player }, ItemFilter::new().damaged())
             }
-        }
+            ItemWindowMode::StorageTakeOut { cid } => (ItemListLocation::Shop { cid }, ItemFilter::all()),
+            ItemWindowMode::StoragePutIn { .. } => (player_ill, ItemFilter::all()),
+            ItemWindowMode::ContainerTakeOut { ill } => (ill, ItemFilter::all()),
+            ItemWindowMode::ContainerPutIn { .. } => (player_ill, ItemFilter::all()),
+            ItemWindowMode::Select { ill, filter, ..} => (ill, filter),
+        };
+        // Filter by the current tab. Note that ItemFilter::all() skips all criteria.
+        let filter = if let Some(kind_rough) = ITEM_TABS[self.tab] {
+            let mut filter = filter.kind_rough(kind_rough);
+            filter.all = false;
+            filter
+        } else {
+            filter
+        };
+        let filtered_list = gd.get_filtered_item_list(ill, filter);
+        self.update_list(filtered_list, gd);
 
         self.page_window.set_page(self.list.get_page(), self.list.get_max_page());
+        self.weight_label.set_text(&weight_text(gd.chara.get(CharaId::Player)));
+        let tab_text: Cow<str> = match ITEM_TABS[self.tab] {
+            Some(ref kind_rough) => kind_rough.to_text(),
+            None => ::text::ui_txt("item_window.tab_all").into(),
+        };
+        let tab_text = replace_str!(
+            ::text::ui_txt("item_window.tab"); tab=tab_text, sort=self.sort_kind);
+        self.tab_label.set_text(&tab_text);
     }
 
-    fn update_list(&mut self, list: FilteredItemList) {
-        self.list.set_n_item(list.clone().count() as u32);
+    fn update_list(&mut self, list: FilteredItemList, gd: &GameData) {
+        let player = gd.chara.get(CharaId::Player);
+        let mut list: Vec<(ItemLocation, &Item, u32)> = list.collect();
+        sort_items(&mut list, self.sort_kind);
+        self.list.set_n_item(list.len() as u32);
+        if let Some(il) = self.item_to_select.take() {
+            if let Some(i) = list.iter().position(|&(item_location, _, _)| item_location == il) {
+                self.list.set_current_choice(i as u32);
+            }
+        }
         let list = &list;
         
         let item_locations = &mut self.item_locations;
@@ -125,19 +192,33 @@ impl ItemWindow {
             let mut rows = Vec::new();
             item_locations.clear();
 
-            for (item_location, item, n_item) in list.clone().skip(start as usize).take(page_size as usize) {
-                let item_text = format!(
-                    "{} x {}",
-                    item.to_text(),
-                    n_item);
+            for &(item_location, item, n_item) in list.iter().skip(start as usize).take(page_size as usize) {
+                let mark = if item.flags.contains(ItemFlags::FAVORITE) {
+                    Some(::text::ui_txt("item_window.favorite_mark"))
+                } else if item.flags.contains(ItemFlags::JUNK) {
+                    Some(::text::ui_txt("item_window.junk_mark"))
+                } else {
+                    None
+                };
+                let item_text = if let Some(mark) = mark {
+                    format!("{} {} x {}", mark, item.to_text(), n_item)
+                } else {
+                    format!("{} x {}", item.to_text(), n_item)
+                };
 
                 // Infomation displayed in the right column
                 let additional_info = match mode {
-                    ItemWindowMode::ShopBuy { .. } => {
-                        format!("{}G", item.price())
+                    ItemWindowMode::ShopBuy { cid } => {
+                        format!("{}G", ::game::shop::buy_price(item, player, gd.get_shop(*cid)))
                     }
-                    ItemWindowMode::ShopSell => {
-                        format!("{}G", item.selling_price())
+                    ItemWindowMode::ShopSell { cid } => {
+                        format!("{}G", ::game::shop::sell_price(item, player, gd.get_shop(*cid)))
+                    }
+                    ItemWindowMode::ShopIdentify { .. } => {
+                        format!("{}G", ::game::item::identify::identify_fee(item))
+                    }
+                    ItemWindowMode::ShopRepair { .. } => {
+                        format!("{}G", ::game::item::durability::repair_fee(item))
                     }
                     _ => {
                         format!("{:.2}kg", item.w() as f32 / 1000.0)
@@ -154,7 +235,27 @@ impl ItemWindow {
     fn do_action_for_item(&mut self, pa: &mut DoPlayerAction, il: ItemLocation) -> DialogResult {
         match self.mode {
             ItemWindowMode::List => {
-                DialogResult::Continue
+                if let Some(ill) = pa.open_container(il) {
+                    let container_window = ItemWindow::new(ItemWindowMode::ContainerTakeOut { ill }, pa);
+                    return DialogResult::OpenChildDialog(Box::new(container_window));
+                }
+                if pa.gd().get_item(il).0.kind == ItemKind::RepairKit {
+                    // Callback function for selected equipment repair
+                    let repair_selected_item = move |pa: &mut DoPlayerAction, target: ItemLocation| {
+                        pa.repair_item_with_kit(il, target);
+                        DialogResult::CloseAll
+                    };
+
+                    let select_window = ItemWindow::new_select(
+                        ItemListLocation::Equip { cid: CharaId::Player },
+                        ItemFilter::new().damaged(),
+                        Box::new(repair_selected_item),
+                        pa
+                    );
+                    return DialogResult::OpenChildDialog(Box::new(select_window));
+                }
+                let info_window = ItemInfoWindow::new(&pa.gd().get_item(il).0);
+                DialogResult::OpenChildDialog(Box::new(info_window))
             }
             ItemWindowMode::PickUp => {
                 pa.pick_up_item(il, 1);
@@ -181,13 +282,43 @@ impl ItemWindow {
                 self.update_by_mode(pa);
                 DialogResult::CloseAll
             }
-            ItemWindowMode::ShopBuy { .. } => {
-                pa.buy_item(il);
+            ItemWindowMode::ShopBuy { .. } | ItemWindowMode::ShopSell { .. } => {
+                let n = pa.gd().get_item(il).1;
+                if n > 1 {
+                    self.trading_item = Some(il);
+                    return DialogResult::OpenChildDialog(Box::new(QuantityDialog::new(n)));
+                }
+                self.trade_confirm_dialog(pa, il, ItemMoveNum::All)
+            }
+            ItemWindowMode::ShopIdentify { cid } => {
+                pa.identify_item_at_shop(il, cid);
+                self.update_by_mode(pa);
+                DialogResult::Continue
+            }
+            ItemWindowMode::ShopRepair { cid } => {
+                pa.repair_item_at_shop(il, cid);
+                self.update_by_mode(pa);
+                DialogResult::Continue
+            }
+            ItemWindowMode::StorageTakeOut { .. } => {
+                pa.withdraw_item(il);
+                self.update_by_mode(pa);
+                DialogResult::Continue
+            }
+            ItemWindowMode::StoragePutIn { cid } => {
+                pa.deposit_item(il, cid);
+                self.update_by_mode(pa);
+                DialogResult::Continue
+            }
+            ItemWindowMode::ContainerTakeOut { .. } => {
+                let ill = pa.take_out_item(il, 1);
+                self.mode = ItemWindowMode::ContainerTakeOut { ill };
                 self.update_by_mode(pa);
                 DialogResult::Continue
             }
-            ItemWindowMode::ShopSell => {
-                pa.sell_item(il);
+            ItemWindowMode::ContainerPutIn { ill } => {
+                let ill = pa.put_in_item(il, ill, 1);
+                self.mode = ItemWindowMode::ContainerPutIn { ill };
                 self.update_by_mode(pa);
                 DialogResult::Continue
             }
@@ -196,6 +327,120 @@ impl ItemWindow {
             }
         }
     }
+
+    /// Show the total price and the resulting money, and trade if the player accepts.
+    /// The dialog is not opened if the buyer does not have enough money.
+    fn trade_confirm_dialog(&self, pa: &mut DoPlayerAction, il: ItemLocation, n: ItemMoveNum)
+                            -> DialogResult {
+        let gd = pa.gd();
+        let (item, n_item) = gd.get_item(il);
+        let n_trade = n.to_u32(n_item);
+        let money = gd.player.money();
+
+        let msg_dialog = match self.mode {
+            ItemWindowMode::ShopBuy { .. } => {
+                let price = ::game::shop::buy_total(gd, il, n_trade);
+                if !gd.player.has_money(price) {
+                    game_log_i!("shop-lack-of-money"; chara=gd.chara.get(CharaId::Player));
+                    return DialogResult::Continue;
+                }
+                let after = money - price;
+                let msg = replace_str!(
+                    ::text::ui_txt("dialog.buy_item");
+                    item=item, n=n_trade, price=price, money=money, after=after);
+                MsgDialog::with_yesno(&msg, move |pa, answer| {
+                    if answer == 0 {
+                        pa.buy_item(il, n);
+                    }
+                    DialogResult::Close
+                })
+            }
+            ItemWindowMode::ShopSell { cid } => {
+                let price = ::game::shop::sell_total(gd, il, cid, n_trade);
+                if gd.get_shop(cid).money < price {
+                    game_log_i!("shop-lack-of-shop-money"; chara=gd.chara.get(cid));
+                    return DialogResult::Continue;
+                }
+                let after = money + price;
+                let msg = replace_str!(
+                    ::text::ui_txt("dialog.sell_item");
+                    item=item, n=n_trade, price=price, money=money, after=after);
+                MsgDialog::with_yesno(&msg, move |pa, answer| {
+                    if answer == 0 {
+                        pa.sell_item(il, cid, n);
+                    }
+                    DialogResult::Close
+                })
+            }
+            _ => unreachable!(),
+        };
+        DialogResult::OpenChildDialog(Box::new(msg_dialog))
+    }
+
+    /// Confirm selling all items marked as junk
+    fn sell_junk_dialog(&self, pa: &mut DoPlayerAction) -> DialogResult {
+        let cid = match self.mode {
+            ItemWindowMode::ShopSell { cid } => cid,
+            _ => { return DialogResult::Continue; }
+        };
+        let gd = pa.gd();
+        let (n, price) = ::game::shop::junk_total(gd, cid);
+        if n == 0 {
+            return DialogResult::Continue;
+        }
+        let money = gd.player.money();
+        let after = money + price;
+        let msg = replace_str!(
+            ::text::ui_txt("dialog.sell_junk"); n=n, price=price, money=money, after=after);
+        let msg_dialog = MsgDialog::with_yesno(&msg, move |pa, answer| {
+            if answer == 0 {
+                pa.sell_junk(cid);
+            }
+            DialogResult::Close
+        });
+        DialogResult::OpenChildDialog(Box::new(msg_dialog))
+    }
+
+    /// The selected item if it is in the player's inventory
+    fn selected_player_item(&self) -> Option<ItemLocation> {
+        let i = self.list.get_current_choice() - self.list.get_page() * UI_CFG.item_window.n_row;
+        let il = *self.item_locations.get(i as usize)?;
+        if il.0 == (ItemListLocation::Chara { cid: CharaId::Player }) {
+            Some(il)
+        } else {
+            None
+        }
+    }
+
+    fn switch_tab(&mut self, pa: &mut DoPlayerAction, right: bool) {
+        self.tab = if right {
+            (self.tab + 1) % ITEM_TABS.len()
+        } else {
+            (self.tab + ITEM_TABS.len() - 1) % ITEM_TABS.len()
+        };
+        self.list.set_page(0);
+        self.update_by_mode(pa);
+    }
+
+    fn is_container_mode(&self) -> bool {
+        match self.mode {
+            ItemWindowMode::ContainerTakeOut { .. } | ItemWindowMode::ContainerPutIn { .. }
+            | ItemWindowMode::StorageTakeOut { .. } | ItemWindowMode::StoragePutIn { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Switch taking out and putting in for containers and storages
+    fn switch_container_mode(&mut self, pa: &mut DoPlayerAction) {
+        self.mode = match self.mode {
+            ItemWindowMode::ContainerTakeOut { ill } => ItemWindowMode::ContainerPutIn { ill },
+            ItemWindowMode::ContainerPutIn { ill } => ItemWindowMode::ContainerTakeOut { ill },
+            ItemWindowMode::StorageTakeOut { cid } => ItemWindowMode::StoragePutIn { cid },
+            ItemWindowMode::StoragePutIn { cid } => ItemWindowMode::StorageTakeOut { cid },
+            _ => { return; }
+        };
+        self.update_by_mode(pa);
+    }
 }
 
 impl Window for ItemWindow {
@@ -206,6 +451,8 @@ impl Window for ItemWindow {
         
         draw_rect_border(canvas, self.rect);
         self.list.draw(canvas, sv);
+        self.weight_label.draw(canvas, sv);
+        self.tab_label.draw(canvas, sv);
         self.page_window.draw(canvas, game, sv, anim);
     }
 }
@@ -230,10 +477,68 @@ impl DialogWindow for ItemWindow {
             Command::Cancel => {
                 DialogResult::Close
             },
+            Command::RotateWindowRight | Command::RotateWindowLeft => {
+                if self.is_container_mode() {
+                    self.switch_container_mode(pa);
+                } else {
+                    self.switch_tab(pa, *command == Command::RotateWindowRight);
+                }
+                DialogResult::Continue
+            }
+            Command::SellJunk => {
+                self.sell_junk_dialog(pa)
+            }
+            Command::SortItems => {
+                self.sort_kind = self.sort_kind.next();
+                self.update_by_mode(pa);
+                DialogResult::Continue
+            }
+            Command::SplitItem => {
+                if let Some(il) = self.selected_player_item() {
+                    let n = pa.gd().get_item(il).1;
+                    if n > 1 {
+                        self.splitting_item = Some(il);
+                        return DialogResult::OpenChildDialog(Box::new(QuantityDialog::new(n - 1)));
+                    }
+                }
+                DialogResult::Continue
+            }
+            Command::MarkFavorite | Command::MarkJunk => {
+                if let Some(il) = self.selected_player_item() {
+                    let mark = if *command == Command::MarkFavorite {
+                        ItemFlags::FAVORITE
+                    } else {
+                        ItemFlags::JUNK
+                    };
+                    self.item_to_select = Some(pa.toggle_item_mark(il, mark));
+                    self.update_by_mode(pa);
+                }
+                DialogResult::Continue
+            }
             _ => DialogResult::Continue,
         }
     }
 
+    fn callback_child_closed(
+        &mut self, result: Option<Box<Any>>, pa: &mut DoPlayerAction) -> DialogResult {
+        let n = result.and_then(|v| v.downcast::<ItemMoveNum>().ok());
+        // The quantity to buy or sell is chosen
+        if let Some(il) = self.trading_item.take() {
+            if let Some(n) = n {
+                return self.trade_confirm_dialog(pa, il, *n);
+            }
+        }
+        // The quantity to split is chosen
+        if let Some(il) = self.splitting_item.take() {
+            if let Some(n) = n {
+                let n = n.to_u32(pa.gd().get_item(il).1 - 1);
+                pa.split_item(il, n);
+            }
+        }
+        self.update_by_mode(pa);
+        DialogResult::Continue
+    }
+
     fn mode(&self) -> InputMode {
         InputMode::Dialog
     }
@@ -7,6 +7,7 @@ mod equip_window;
 mod exit_window;
 mod group_window;
 mod item_window;
+mod item_info_window;
 mod log_window;
 mod main_window;
 mod talk_window;
@@ -16,8 +17,11 @@ mod msg_dialog;
 mod newgame_window;
 mod start_window;
 mod status_window;
+mod spell_window;
+mod craft_window;
 mod game_info_window;
 mod text_input_dialog;
+mod quantity_dialog;
 mod indicator;
 mod minimap;
 
@@ -295,6 +299,8 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
                 // If player is on stairs, move from this map
                 if pa.gd().on_map_entrance() {
                     pa.goto_next_floor(Direction::none());
+                } else {
+                    pa.pull_lever();
                 }
             }
             Command::Shot => {
@@ -330,6 +336,23 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
             Command::EatItem => {
                 self.window_stack.push(Box::new(ItemWindow::new(ItemWindowMode::Eat, &mut pa)));
             }
+            Command::Cast => {
+                if pa.gd().chara.get(CharaId::Player).spells.is_empty() {
+                    game_log_i!("no-spell-learned");
+                } else {
+                    self.window_stack.push(Box::new(spell_window::SpellWindow::new(&mut pa)));
+                }
+            }
+            Command::Craft => {
+                if ::game::craft::available_recipes(pa.gd()).is_empty() {
+                    game_log_i!("no-recipe-available");
+                } else {
+                    self.window_stack.push(Box::new(craft_window::CraftWindow::new(&mut pa)));
+                }
+            }
+            Command::CloseDoor => {
+                pa.close_door();
+            }
             Command::TargetingMode => {
                 self.targeting_mode = true;
                 match self.mode {
@@ -404,6 +427,9 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
         match command {
             Command::Move{ dir } => {
                 main_window.move_centering_tile(dir, &self.game);
+                // Show the danger of the site under the cursor on region maps
+                let ct = main_window.get_current_centering_tile();
+                ::game::danger::report_site_danger(&self.game.gd, ct);
             }
             Command::Cancel => {
                 self.targeting_mode = false;
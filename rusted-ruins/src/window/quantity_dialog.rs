@@ -0,0 +1,79 @@
+
+use config::UI_CFG;
+use sdlvalues::FontKind;
+use array2d::*;
+use common::gamedata::ItemMoveNum;
+use super::commonuse::*;
+use super::widget::*;
+
+/// Choose the number of items to handle.
+/// Returns the chosen number as ItemMoveNum when closed.
+pub struct QuantityDialog {
+    label: LabelWidget,
+    rect: Rect,
+    n: u32,
+    max: u32,
+}
+
+impl QuantityDialog {
+    pub fn new(max: u32) -> QuantityDialog {
+        let rect: Rect = UI_CFG.quantity_dialog.rect.into();
+        let label_rect = Rect::new(0, 0, rect.width(), rect.height());
+        
+        let mut dialog = QuantityDialog {
+            label: LabelWidget::new(label_rect, "", FontKind::M),
+            rect: rect,
+            n: max,
+            max: max,
+        };
+        dialog.update_label();
+        dialog
+    }
+
+    fn update_label(&mut self) {
+        let text = replace_str!(::text::ui_txt("dialog.quantity"); n=self.n, max=self.max);
+        self.label.set_text(&text);
+    }
+}
+
+impl Window for QuantityDialog {
+    fn draw(
+        &mut self, canvas: &mut WindowCanvas, _game: &Game, sv: &mut SdlValues,
+        _anim: Option<(&Animation, u32)>) {
+        
+        draw_rect_border(canvas, self.rect);
+        self.label.draw(canvas, sv);
+    }
+}
+
+impl DialogWindow for QuantityDialog {
+    fn process_command(&mut self, command: &Command, _pa: &mut DoPlayerAction) -> DialogResult {
+        match *command {
+            Command::Move { dir } => {
+                let increase = dir.vdir == VDirection::Up || dir.hdir == HDirection::Right;
+                let decrease = dir.vdir == VDirection::Down || dir.hdir == HDirection::Left;
+                if increase {
+                    self.n = if self.n >= self.max { 1 } else { self.n + 1 };
+                } else if decrease {
+                    self.n = if self.n <= 1 { self.max } else { self.n - 1 };
+                }
+                self.update_label();
+                DialogResult::Continue
+            }
+            Command::Enter => {
+                let n = if self.n == self.max {
+                    ItemMoveNum::All
+                } else {
+                    ItemMoveNum::Partial(self.n)
+                };
+                DialogResult::CloseWithValue(Box::new(n))
+            }
+            Command::Cancel => DialogResult::Close,
+            _ => DialogResult::Continue,
+        }
+    }
+
+    fn mode(&self) -> InputMode {
+        InputMode::Dialog
+    }
+}
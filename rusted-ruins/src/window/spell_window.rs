@@ -0,0 +1,89 @@
+
+use config::UI_CFG;
+use common::gobj;
+use common::gamedata::*;
+use super::commonuse::*;
+use super::widget::*;
+use text;
+
+/// Player chooses a spell to cast
+pub struct SpellWindow {
+    rect: Rect,
+    list: ListWidget,
+    spells: Vec<usize>,
+}
+
+impl SpellWindow {
+    pub fn new(pa: &mut DoPlayerAction) -> SpellWindow {
+        let rect = UI_CFG.spell_window.rect.into();
+
+        let mut spell_window = SpellWindow {
+            rect: rect,
+            list: ListWidget::new(
+                (0i32, 0i32, rect.w as u32, rect.h as u32), ListRowKind::Str,
+                vec![0], Some(UI_CFG.spell_window.n_row), 26),
+            spells: Vec::new(),
+        };
+        spell_window.update_list(pa);
+        spell_window
+    }
+
+    fn update_list(&mut self, pa: &mut DoPlayerAction) {
+        let chara = pa.gd().chara.get(CharaId::Player);
+        self.list.set_n_item(chara.spells.len() as u32);
+        let spells = &mut self.spells;
+
+        self.list.update_rows_by_func(|start, page_size| {
+            let mut rows = Vec::new();
+            spells.clear();
+            for (i, spell_idx) in chara.spells.iter().enumerate()
+                .skip(start as usize).take(page_size as usize) {
+
+                let spell = gobj::get_obj(*spell_idx);
+                let s = format!("{}  ({} MP)", text::obj_txt(&spell.id), spell.cost);
+                rows.push(ListRow::Str(s));
+                spells.push(i);
+            }
+            rows
+        });
+    }
+}
+
+impl Window for SpellWindow {
+    fn draw(
+        &mut self, canvas: &mut WindowCanvas, _game: &Game, sv: &mut SdlValues,
+        _anim: Option<(&Animation, u32)>) {
+
+        draw_rect_border(canvas, self.rect);
+        self.list.draw(canvas, sv);
+    }
+}
+
+impl DialogWindow for SpellWindow {
+    fn process_command(&mut self, command: &Command, pa: &mut DoPlayerAction) -> DialogResult {
+        if let Some(response) = self.list.process_command(&command) {
+            match response {
+                ListWidgetResponse::Select(i) => { // Any spell is selected
+                    pa.cast_spell(self.spells[i as usize]);
+                    return DialogResult::Close;
+                }
+                ListWidgetResponse::PageChanged => {
+                    self.update_list(pa);
+                }
+                _ => (),
+            }
+            return DialogResult::Continue;
+        }
+
+        match *command {
+            Command::Cancel => {
+                DialogResult::Close
+            }
+            _ => DialogResult::Continue,
+        }
+    }
+
+    fn mode(&self) -> InputMode {
+        InputMode::Dialog
+    }
+}
@@ -5,6 +5,7 @@ use sdlvalues::FontKind;
 use config::UI_CFG;
 use common::gamedata::*;
 use text::ToText;
+use game::extrait::CharaEx;
 use super::group_window::GroupWindow;
 use super::choose_window::PagedChooseWindow;
 
@@ -28,12 +29,15 @@ pub struct StatusWindow {
     image: ImageWidget,
     name_label: LabelWidget,
     hp_label: LabelWidget,
+    mp_label: LabelWidget,
     str_label: LabelWidget,
     vit_label: LabelWidget,
     dex_label: LabelWidget,
     int_label: LabelWidget,
     wil_label: LabelWidget,
     cha_label: LabelWidget,
+    weight_label: LabelWidget,
+    status_label: LabelWidget,
 }
 
 impl StatusWindow {
@@ -45,6 +49,8 @@ impl StatusWindow {
         let name_label = LabelWidget::new(cfg.name_label_rect, &chara.to_text(), FontKind::M);
         let hp_label = LabelWidget::new(
             cfg.hp_label_rect, &format!("HP  {} / {}", chara.hp, chara.params.max_hp), FontKind::MonoM);
+        let mp_label = LabelWidget::new(
+            cfg.mp_label_rect, &format!("MP  {} / {}", chara.mp, chara.params.max_mp), FontKind::MonoM);
         let str_label = LabelWidget::new(
             cfg.str_label_rect, &format!("STR  {}", chara.params.str), FontKind::MonoM);
         let vit_label = LabelWidget::new(
@@ -57,14 +63,29 @@ impl StatusWindow {
             cfg.wil_label_rect, &format!("WIL  {}", chara.params.wil), FontKind::MonoM);
         let cha_label = LabelWidget::new(
             cfg.cha_label_rect, &format!("CHA  {}", chara.params.cha), FontKind::MonoM);
+        let weight_label = LabelWidget::new(
+            cfg.weight_label_rect, &weight_text(chara), FontKind::MonoM);
+        let status_text = chara.status.iter()
+            .map(|s| ::text::to_txt(s))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let status_label = LabelWidget::wrapped(
+            cfg.status_label_rect, &status_text, FontKind::M, cfg.status_label_w);
         StatusWindow {
             rect,
-            image, name_label, hp_label,
+            image, name_label, hp_label, mp_label,
             str_label, vit_label, dex_label, int_label, wil_label, cha_label,
+            weight_label, status_label,
         }
     }
 }
 
+/// Carrying weight and capacity in kg
+pub fn weight_text(chara: &Chara) -> String {
+    format!("WEIGHT  {:.1} / {:.1} kg",
+            chara.carrying_w() as f32 / 1000.0, chara.carry_capacity() as f32 / 1000.0)
+}
+
 impl Window for StatusWindow {
     fn draw(
         &mut self, canvas: &mut WindowCanvas, _game: &Game, sv: &mut SdlValues,
@@ -74,12 +95,15 @@ impl Window for StatusWindow {
         self.image.draw(canvas, sv);
         self.name_label.draw(canvas, sv);
         self.hp_label.draw(canvas, sv);
+        self.mp_label.draw(canvas, sv);
         self.str_label.draw(canvas, sv);
         self.vit_label.draw(canvas, sv);
         self.dex_label.draw(canvas, sv);
         self.int_label.draw(canvas, sv);
         self.wil_label.draw(canvas, sv);
         self.cha_label.draw(canvas, sv);
+        self.weight_label.draw(canvas, sv);
+        self.status_label.draw(canvas, sv);
     }
 }
 
@@ -172,6 +172,17 @@ impl ListWidget {
         self.max_page
     }
 
+    /// Set current choice
+    /// The page is changed to the one including the choice
+    pub fn set_current_choice(&mut self, choice: u32) {
+        if let Some(page_size) = self.page_size {
+            self.current_page = choice / page_size;
+            self.current_choice = choice % page_size;
+        } else {
+            self.current_choice = choice;
+        }
+    }
+
     /// Get current choice
     /// This function considers current page position
     pub fn get_current_choice(&self) -> u32 {